@@ -0,0 +1,116 @@
+use crate::matrices::Matrix44::*;
+use crate::quaternions::Quaternion::*;
+use crate::vectors::{VectorProperties::*, Vector3::*};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Projection {
+    // `fovY` is the vertical field of view in radians.
+    Perspective { fovY: f64, near: f64, far: f64 },
+    // `height` is the extent of the view volume along y in world units.
+    Orthographic { height: f64, near: f64, far: f64 }
+}
+
+// The orientation turns the camera's own axes into world space: it looks
+// down its -z with +y up and +x to the right.
+pub struct Camera {
+    position: Vector3,
+    orientation: Quaternion,
+    projection: Projection,
+    aspect: f64
+}
+
+impl Camera {
+    // Starts at `position` looking down -z with +y up. `aspect` is the
+    // physical width/height of the viewport, see GeneralMath::terminalAspect.
+    pub fn new(position: Vector3, projection: Projection, aspect: f64) -> Camera {
+        Camera {
+            position,
+            orientation: Quaternion::default(),
+            projection,
+            aspect
+        }
+    }
+
+    pub fn getPosition(&self) -> &Vector3 {
+        &self.position
+    }
+
+    pub fn setPosition(&mut self, position: &Vector3) {
+        self.position = *position;
+    }
+
+    pub fn getOrientation(&self) -> &Quaternion {
+        &self.orientation
+    }
+
+    pub fn setOrientation(&mut self, orientation: &Quaternion) {
+        self.orientation = Quaternion::getNormalized(orientation);
+    }
+
+    pub fn getForward(&self) -> Vector3 {
+        self.orientation.rotate(&Vector3::new(0.0, 0.0, -1.0))
+    }
+
+    pub fn getUp(&self) -> Vector3 {
+        self.orientation.rotate(&Vector3::new(0.0, 1.0, 0.0))
+    }
+
+    pub fn getRight(&self) -> Vector3 {
+        self.orientation.rotate(&Vector3::new(1.0, 0.0, 0.0))
+    }
+
+    // Faces along `forward` with the camera's up as close to `up` as
+    // possible.
+    pub fn lookAlong(&mut self, forward: &Vector3, up: &Vector3) {
+        let back: Vector3 = Vector3::new(-forward.x, -forward.y, -forward.z);
+        self.orientation.lookRotation(&back, up);
+    }
+
+    // Turns towards `target`, keeping the current up as far as it can.
+    pub fn lookAt(&mut self, target: &Vector3) {
+        let forward: Vector3 = *target - self.position;
+
+        if forward.length() > 0.0 {
+            let up = self.getUp();
+            self.lookAlong(&forward, &up);
+        }
+    }
+
+    pub fn getProjection(&self) -> &Projection {
+        &self.projection
+    }
+
+    pub fn setProjection(&mut self, projection: &Projection) {
+        self.projection = *projection;
+    }
+
+    pub fn getAspect(&self) -> f64 {
+        self.aspect
+    }
+
+    pub fn setAspect(&mut self, aspect: f64) {
+        self.aspect = aspect;
+    }
+
+    // The inverse of the camera's placement: undo the position, then the
+    // orientation.
+    pub fn getViewMatrix(&self) -> Matrix44 {
+        let mut view: Matrix44 = self.orientation.conjugate().toMatrix();
+        view.translate(&Vector3::new(-self.position.x, -self.position.y, -self.position.z));
+        view
+    }
+
+    pub fn getProjectionMatrix(&self) -> Matrix44 {
+        match self.projection {
+            Projection::Perspective { fovY, near, far } => {
+                Matrix44::perspective(fovY, self.aspect, near, far)
+            },
+            Projection::Orthographic { height, near, far } => {
+                let halfHeight: f64 = height * 0.5;
+                let halfWidth: f64 = halfHeight * self.aspect;
+
+                Matrix44::orthographic(-halfWidth, halfWidth, -halfHeight, halfHeight, near, far)
+            }
+        }
+    }
+}
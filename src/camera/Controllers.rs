@@ -0,0 +1,178 @@
+use crate::camera::Camera::*;
+use crate::quaternions::Quaternion::*;
+use crate::vectors::{VectorProperties::*, Vector3::*};
+use crate::math_utils::GeneralMath;
+use pancurses::Input;
+
+// Keeps first person pitch just short of straight up/down so the view never
+// flips.
+const PITCH_LIMIT: f64 = 89.0;
+
+// Unit direction for the given yaw and pitch in radians. Yaw 0 faces -z and
+// positive yaw turns towards +x.
+fn direction(yaw: f64, pitch: f64) -> Vector3 {
+    Vector3::new(pitch.cos() * yaw.sin(), pitch.sin(), -pitch.cos() * yaw.cos())
+}
+
+// Maps a point in [-1, 1] view coordinates, +y up, onto the unit arcball.
+// Points off the ball land on its rim.
+fn arcballPoint(x: f64, y: f64) -> Vector3 {
+    let d: f64 = x * x + y * y;
+
+    if d <= 1.0 {
+        Vector3::new(x, y, (1.0 - d).sqrt())
+    } else {
+        Vector3::getNormalized(&Vector3::new(x, y, 0.0))
+    }
+}
+
+// Orbits the camera around a target point at a fixed distance, arcball
+// style. Rotations turn about the view's own axes and build up in a
+// quaternion, so the camera goes over the poles rather than stopping at
+// them. Arrow keys or WASD rotate, +/- zoom.
+pub struct OrbitController {
+    pub target: Vector3,
+    pub distance: f64,
+    // The camera's orientation; it sits on its own +z axis from the target.
+    pub orientation: Quaternion,
+    pub rotateStep: f64,
+    pub zoomStep: f64,
+    pub minDistance: f64
+}
+
+impl OrbitController {
+    pub fn new(target: Vector3, distance: f64) -> OrbitController {
+        OrbitController {
+            target,
+            distance,
+            orientation: Quaternion::default(),
+            rotateStep: GeneralMath::toRadians(5.0),
+            zoomStep: 0.25,
+            minDistance: 0.1
+        }
+    }
+
+    // Positive yaw swings the camera to the left around the target and
+    // positive pitch swings it down, both about the current view axes.
+    pub fn rotate(&mut self, deltaYaw: f64, deltaPitch: f64) {
+        let yaw = Quaternion::fromAxisAngle(&Vector3::new(0.0, 1.0, 0.0), -deltaYaw);
+        let pitch = Quaternion::fromAxisAngle(&Vector3::new(1.0, 0.0, 0.0), deltaPitch);
+
+        self.turn(&(yaw * pitch));
+    }
+
+    // Drags the arcball from one view point to another, both in [-1, 1]
+    // with +y up. The scene follows the drag, so the camera turns the other
+    // way.
+    pub fn drag(&mut self, from: (f64, f64), to: (f64, f64)) {
+        let a: Vector3 = arcballPoint(from.0, from.1);
+        let b: Vector3 = arcballPoint(to.0, to.1);
+        let axis: Vector3 = Vector3::cross(&a, &b);
+
+        if axis.length() < 1e-12 {
+            return;
+        }
+
+        let angle: f64 = (a * b).clamp(-1.0, 1.0).acos();
+        self.turn(&Quaternion::fromAxisAngle(&axis, -angle));
+    }
+
+    // Applies a rotation given in view space.
+    fn turn(&mut self, rotation: &Quaternion) {
+        self.orientation = Quaternion::getNormalized(&(self.orientation * *rotation));
+    }
+
+    pub fn zoom(&mut self, delta: f64) {
+        self.distance = (self.distance + delta).max(self.minDistance);
+    }
+
+    // Places the camera on the orbit sphere, looking at the target.
+    pub fn apply(&self, camera: &mut Camera) {
+        let offset: Vector3 = self.orientation.rotate(&Vector3::new(0.0, 0.0, self.distance));
+
+        camera.setPosition(&(self.target + offset));
+        camera.setOrientation(&self.orientation);
+    }
+
+    // Returns true when the key was consumed and the camera updated.
+    pub fn handleInput(&mut self, input: &Input, camera: &mut Camera) -> bool {
+        match input {
+            Input::KeyLeft | Input::Character('a') => self.rotate(self.rotateStep, 0.0),
+            Input::KeyRight | Input::Character('d') => self.rotate(-self.rotateStep, 0.0),
+            Input::KeyUp | Input::Character('w') => self.rotate(0.0, -self.rotateStep),
+            Input::KeyDown | Input::Character('s') => self.rotate(0.0, self.rotateStep),
+            Input::Character('+') | Input::Character('=') => self.zoom(-self.zoomStep),
+            Input::Character('-') => self.zoom(self.zoomStep),
+            _ => return false
+        }
+
+        self.apply(camera);
+        true
+    }
+}
+
+// Free-look camera: arrow keys turn, WASD moves, r/f rise and fall.
+pub struct FirstPersonController {
+    pub yaw: f64,
+    pub pitch: f64,
+    pub turnStep: f64,
+    pub moveStep: f64
+}
+
+impl Default for FirstPersonController {
+    fn default() -> FirstPersonController {
+        FirstPersonController {
+            yaw: 0.0,
+            pitch: 0.0,
+            turnStep: GeneralMath::toRadians(5.0),
+            moveStep: 0.25
+        }
+    }
+}
+
+impl FirstPersonController {
+    pub fn turn(&mut self, deltaYaw: f64, deltaPitch: f64) {
+        let limit: f64 = GeneralMath::toRadians(PITCH_LIMIT);
+
+        self.yaw += deltaYaw;
+        self.pitch = (self.pitch + deltaPitch).max(-limit).min(limit);
+    }
+
+    // Moves relative to the current heading, ignoring pitch so walking
+    // forward stays level.
+    pub fn translate(&self, camera: &mut Camera, forward: f64, right: f64, up: f64) {
+        let heading: Vector3 = direction(self.yaw, 0.0);
+        let side: Vector3 = Vector3::cross(&heading, &Vector3::new(0.0, 1.0, 0.0));
+
+        let mut offset: Vector3 = Vector3::new(0.0, up, 0.0);
+        offset += Vector3::new(heading.x * forward, 0.0, heading.z * forward);
+        offset += Vector3::new(side.x * right, 0.0, side.z * right);
+
+        let position: Vector3 = *camera.getPosition() + offset;
+        camera.setPosition(&position);
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.lookAlong(&direction(self.yaw, self.pitch), &Vector3::new(0.0, 1.0, 0.0));
+    }
+
+    // Returns true when the key was consumed and the camera updated.
+    pub fn handleInput(&mut self, input: &Input, camera: &mut Camera) -> bool {
+        match input {
+            Input::KeyLeft => self.turn(-self.turnStep, 0.0),
+            Input::KeyRight => self.turn(self.turnStep, 0.0),
+            Input::KeyUp => self.turn(0.0, self.turnStep),
+            Input::KeyDown => self.turn(0.0, -self.turnStep),
+            Input::Character('w') => self.translate(camera, self.moveStep, 0.0, 0.0),
+            Input::Character('s') => self.translate(camera, -self.moveStep, 0.0, 0.0),
+            Input::Character('a') => self.translate(camera, 0.0, -self.moveStep, 0.0),
+            Input::Character('d') => self.translate(camera, 0.0, self.moveStep, 0.0),
+            Input::Character('r') => self.translate(camera, 0.0, 0.0, self.moveStep),
+            Input::Character('f') => self.translate(camera, 0.0, 0.0, -self.moveStep),
+            _ => return false
+        }
+
+        self.apply(camera);
+        true
+    }
+}
@@ -0,0 +1,2 @@
+pub mod Camera;
+pub mod Controllers;
@@ -1,3 +1,4 @@
-pub const PI: f64 = 3.141592653;
+pub const PI: f64 = std::f64::consts::PI;
 
-use pancurses::{initscr, Window};
\ No newline at end of file
+// Height of a terminal cell relative to its width.
+pub const CELL_ASPECT: f64 = 2.0;
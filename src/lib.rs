@@ -0,0 +1,15 @@
+#![allow(non_snake_case)]
+extern crate pancurses;
+
+pub mod vectors;
+pub mod matrices;
+pub mod quaternions;
+pub mod rasterizer;
+pub mod pipeline;
+pub mod camera;
+pub mod math_utils;
+pub mod mesh;
+pub mod loaders;
+pub mod lighting;
+
+pub mod defs;
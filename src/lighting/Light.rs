@@ -0,0 +1,132 @@
+use crate::vectors::{VectorProperties::*, Vector3::*};
+use crate::rasterizer::Color::*;
+
+// Distance falloff of point and spot lights: 1 / (constant + linear * d +
+// quadratic * d^2).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Attenuation {
+    pub constant: f64,
+    pub linear: f64,
+    pub quadratic: f64
+}
+
+impl Default for Attenuation {
+    // No falloff.
+    fn default() -> Attenuation {
+        Attenuation::new(1.0, 0.0, 0.0)
+    }
+}
+
+impl Attenuation {
+    pub fn new(constant: f64, linear: f64, quadratic: f64) -> Attenuation {
+        Attenuation {
+            constant,
+            linear,
+            quadratic
+        }
+    }
+
+    pub fn factor(&self, distance: f64) -> f64 {
+        let divisor = self.constant + self.linear * distance + self.quadratic * distance * distance;
+
+        if divisor > 0.0 { 1.0 / divisor } else { 1.0 }
+    }
+}
+
+// Light sources in world space. Directions point the way the light travels
+// and spot angles are half-angles in radians, full strength inside
+// `innerAngle` and fading out by `outerAngle`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Light {
+    Ambient {
+        color: Color,
+        intensity: f64
+    },
+    Directional {
+        direction: Vector3,
+        color: Color,
+        intensity: f64
+    },
+    Point {
+        position: Vector3,
+        color: Color,
+        intensity: f64,
+        attenuation: Attenuation
+    },
+    Spot {
+        position: Vector3,
+        direction: Vector3,
+        innerAngle: f64,
+        outerAngle: f64,
+        color: Color,
+        intensity: f64,
+        attenuation: Attenuation
+    }
+}
+
+impl Light {
+    pub fn getColor(&self) -> Color {
+        match self {
+            Light::Ambient { color, .. } | Light::Directional { color, .. }
+                | Light::Point { color, .. } | Light::Spot { color, .. } => *color
+        }
+    }
+
+    pub fn getIntensity(&self) -> f64 {
+        match self {
+            Light::Ambient { intensity, .. } | Light::Directional { intensity, .. }
+                | Light::Point { intensity, .. } | Light::Spot { intensity, .. } => *intensity
+        }
+    }
+
+    // The unit direction from `position` towards the light and the share of
+    // the light's intensity that arrives there, or None for ambient light and
+    // points outside a spot's cone.
+    pub fn incidence(&self, position: &Vector3) -> Option<(Vector3, f64)> {
+        match self {
+            Light::Ambient { .. } => None,
+            Light::Directional { direction, intensity, .. } => {
+                let toLight = Vector3::getNormalized(&Vector3::new(-direction.x, -direction.y, -direction.z));
+                Some((toLight, *intensity))
+            },
+            Light::Point { position: source, intensity, attenuation, .. } => {
+                let offset = *source - *position;
+                let distance = Vector3::length(&offset);
+
+                if distance == 0.0 {
+                    return None;
+                }
+
+                Some((Vector3::getNormalized(&offset), intensity * attenuation.factor(distance)))
+            },
+            Light::Spot { position: source, direction, innerAngle, outerAngle, intensity, attenuation, .. } => {
+                let offset = *source - *position;
+                let distance = Vector3::length(&offset);
+
+                if distance == 0.0 {
+                    return None;
+                }
+
+                let toLight = Vector3::getNormalized(&offset);
+                let axis = Vector3::getNormalized(direction);
+                let cosAngle = -Vector3::dot(&toLight, &axis);
+                let cosInner = innerAngle.cos();
+                let cosOuter = outerAngle.cos();
+
+                let cone = if cosAngle >= cosInner {
+                    1.0
+                } else if cosAngle <= cosOuter {
+                    0.0
+                } else {
+                    (cosAngle - cosOuter) / (cosInner - cosOuter)
+                };
+
+                if cone <= 0.0 {
+                    return None;
+                }
+
+                Some((toLight, intensity * cone * attenuation.factor(distance)))
+            }
+        }
+    }
+}
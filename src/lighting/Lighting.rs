@@ -0,0 +1,191 @@
+use crate::vectors::{VectorProperties::*, Vector3::*};
+use crate::rasterizer::{Color::*, Fragment::*};
+use crate::lighting::Light::*;
+
+// Where lighting is evaluated: once per triangle, once per vertex with the
+// result interpolated, or per fragment from interpolated normals.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ShadingModel {
+    Flat,
+    Gouraud,
+    Phong
+}
+
+// How highlights are computed: from the reflected light direction (Phong)
+// or from the half vector between light and eye (Blinn-Phong).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpecularModel {
+    Phong,
+    BlinnPhong
+}
+
+// Light arriving at a surface point, per color channel. Diffuse includes
+// ambient light and tints the surface color; specular is added on top.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Illumination {
+    pub diffuse: [f64; 3],
+    pub specular: [f64; 3]
+}
+
+impl Illumination {
+    // How bright the light is, for the glyph ramp, regardless of the
+    // surface color.
+    pub fn getIntensity(&self) -> f64 {
+        (luminance(&self.diffuse) + luminance(&self.specular)).clamp(0.0, 1.0)
+    }
+
+    // Lights a surface of color `base`.
+    pub fn shade(&self, base: &Color) -> Shade {
+        let [r, g, b] = base.toFloats();
+
+        Shade::new(Color::fromFloats(
+            r * self.diffuse[0] + self.specular[0],
+            g * self.diffuse[1] + self.specular[1],
+            b * self.diffuse[2] + self.specular[2]), self.getIntensity())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lighting {
+    lights: Vec<Light>,
+    shadingModel: ShadingModel,
+    specularModel: SpecularModel,
+    specular: f64,
+    shininess: f64
+}
+
+impl Default for Lighting {
+    fn default() -> Lighting {
+        Lighting::new()
+    }
+}
+
+impl Lighting {
+    // No lights, Phong shading with Blinn-Phong highlights.
+    pub fn new() -> Lighting {
+        Lighting {
+            lights: Vec::new(),
+            shadingModel: ShadingModel::Phong,
+            specularModel: SpecularModel::BlinnPhong,
+            specular: 0.5,
+            shininess: 32.0
+        }
+    }
+
+    pub fn getLights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    pub fn getLightsMut(&mut self) -> &mut Vec<Light> {
+        &mut self.lights
+    }
+
+    pub fn addLight(&mut self, light: Light) {
+        self.lights.push(light);
+    }
+
+    pub fn getShadingModel(&self) -> ShadingModel {
+        self.shadingModel
+    }
+
+    pub fn setShadingModel(&mut self, shadingModel: ShadingModel) {
+        self.shadingModel = shadingModel;
+    }
+
+    pub fn getSpecularModel(&self) -> SpecularModel {
+        self.specularModel
+    }
+
+    pub fn setSpecularModel(&mut self, specularModel: SpecularModel) {
+        self.specularModel = specularModel;
+    }
+
+    // Strength of highlights; 0 turns them off.
+    pub fn getSpecular(&self) -> f64 {
+        self.specular
+    }
+
+    pub fn setSpecular(&mut self, specular: f64) {
+        self.specular = specular;
+    }
+
+    // Specular exponent; higher values give smaller, sharper highlights.
+    pub fn getShininess(&self) -> f64 {
+        self.shininess
+    }
+
+    pub fn setShininess(&mut self, shininess: f64) {
+        self.shininess = shininess;
+    }
+
+    // Lights a world-space point with the given normal as seen from `eye`.
+    // A zero normal only receives ambient light.
+    pub fn illuminate(&self, position: &Vector3, normal: &Vector3, eye: &Vector3) -> Illumination {
+        let mut result = Illumination {
+            diffuse: [0.0; 3],
+            specular: [0.0; 3]
+        };
+
+        let hasNormal = Vector3::length(normal) > 0.0;
+        let n = if hasNormal { Vector3::getNormalized(normal) } else { *normal };
+        let toEye = *eye - *position;
+        let v = if Vector3::length(&toEye) > 0.0 { Vector3::getNormalized(&toEye) } else { n };
+
+        for light in self.lights.iter() {
+            let color = light.getColor().toFloats();
+
+            let (l, strength) = match light.incidence(position) {
+                Some(incidence) if hasNormal => incidence,
+                _ => {
+                    if let Light::Ambient { intensity, .. } = light {
+                        add(&mut result.diffuse, &color, *intensity);
+                    }
+
+                    continue;
+                }
+            };
+
+            let lambert = Vector3::dot(&n, &l);
+
+            if lambert <= 0.0 {
+                continue;
+            }
+
+            add(&mut result.diffuse, &color, strength * lambert);
+
+            if self.specular <= 0.0 {
+                continue;
+            }
+
+            let highlight = match self.specularModel {
+                SpecularModel::Phong => {
+                    // l reflected about n.
+                    let r = Vector3::new(2.0 * lambert * n.x - l.x, 2.0 * lambert * n.y - l.y, 2.0 * lambert * n.z - l.z);
+                    Vector3::dot(&r, &v)
+                },
+                SpecularModel::BlinnPhong => {
+                    let h = l + v;
+
+                    if Vector3::length(&h) > 0.0 { Vector3::dot(&n, &Vector3::getNormalized(&h)) } else { 0.0 }
+                }
+            };
+
+            if highlight > 0.0 {
+                add(&mut result.specular, &color, strength * self.specular * highlight.powf(self.shininess));
+            }
+        }
+
+        result
+    }
+}
+
+fn add(channels: &mut [f64; 3], color: &[f64; 3], amount: f64) {
+    for (channel, c) in channels.iter_mut().zip(color.iter()) {
+        *channel += c * amount;
+    }
+}
+
+// Rec. 709 weights, so a white light at full strength is 1.
+fn luminance(c: &[f64; 3]) -> f64 {
+    0.2126 * c[0] + 0.7152 * c[1] + 0.0722 * c[2]
+}
@@ -0,0 +1,148 @@
+use crate::vectors::{VectorProperties::*, Vector3::*, Vector4::*};
+use crate::matrices::{MatrixProperties::*, Matrix44::*};
+use crate::pipeline::{Pipeline::*, Clipper::*};
+use crate::rasterizer::{Color::*, Fragment::*, Shader::*};
+use crate::mesh::Mesh::Vertex;
+use crate::lighting::Lighting::*;
+
+// Uniforms of LitShader. Lighting happens in world space, so besides the
+// full transform the shader needs the model matrix, its inverse transpose
+// for normals and the camera position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LitUniforms {
+    pub modelViewProjection: Matrix44,
+    pub model: Matrix44,
+    pub normalMatrix: Matrix44,
+    pub eye: Vector3,
+    pub color: Color,
+    pub lighting: Lighting
+}
+
+impl LitUniforms {
+    // Takes the transforms from the pipeline, recovering the camera position
+    // from the view matrix.
+    pub fn new(pipeline: &Pipeline, color: Color, lighting: &Lighting) -> LitUniforms {
+        let model = *pipeline.getModel();
+
+        let mut normalMatrix = model;
+        normalMatrix.invert();
+        normalMatrix.transpose();
+
+        let mut cameraToWorld = *pipeline.getView();
+        cameraToWorld.invert();
+
+        LitUniforms {
+            modelViewProjection: *pipeline.getModelViewProjection(),
+            model,
+            normalMatrix,
+            eye: Vector3::new(cameraToWorld.m30, cameraToWorld.m31, cameraToWorld.m32),
+            color,
+            lighting: lighting.clone()
+        }
+    }
+
+    pub fn toWorld(&self, position: &Vector3) -> Vector3 {
+        let p = self.model * Vector4::new(position.x, position.y, position.z, 1.0);
+        Vector3::new(p.x, p.y, p.z)
+    }
+
+    // Not normalized, and zero stays zero.
+    pub fn toWorldNormal(&self, normal: &Vector3) -> Vector3 {
+        let n = self.normalMatrix * Vector4::new(normal.x, normal.y, normal.z, 0.0);
+        Vector3::new(n.x, n.y, n.z)
+    }
+}
+
+// Lights mesh vertices tinted by the uniform color. With Phong shading the
+// world position, normal and color are interpolated and lit per fragment;
+// with Gouraud shading each vertex is lit and the result interpolated. Flat
+// shading passes on the same varyings as Phong so that shadeFace can light
+// each triangle from its shaded corners; used as a plain fragment shader it
+// lights per fragment instead.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct LitShader;
+
+impl LitShader {
+    // Lights a whole triangle once, at its centroid, with the normal of its
+    // face. The winding only gives the face's line, so the normal is turned
+    // to the side the vertex normals point to; that way clockwise meshes and
+    // mirrored models are lit like any other. The corners are this shader's
+    // output for a Flat or Phong shading model.
+    pub fn shadeFace(corners: [&ClipVertex; 3], uniforms: &LitUniforms) -> Shade {
+        let world = |c: &ClipVertex| Vector3::new(c.varyings[0], c.varyings[1], c.varyings[2]);
+        let [a, b, c] = [world(corners[0]), world(corners[1]), world(corners[2])];
+        let mut normal = Vector3::cross(&(b - a), &(c - a));
+
+        let smooth = corners.iter().fold(Vector3::new(0.0, 0.0, 0.0), |sum, c| {
+            sum + Vector3::new(c.varyings[3], c.varyings[4], c.varyings[5])
+        });
+
+        if Vector3::length(&normal) == 0.0 {
+            // Degenerate faces fall back to their vertex normals.
+            normal = smooth;
+        } else if normal * smooth < 0.0 {
+            normal = Vector3::new(-normal.x, -normal.y, -normal.z);
+        }
+
+        let centroid = Vector3::new((a.x + b.x + c.x) / 3.0, (a.y + b.y + c.y) / 3.0, (a.z + b.z + c.z) / 3.0);
+
+        let mut base = [0.0; 3];
+
+        for corner in corners.iter() {
+            for (channel, c) in base.iter_mut().zip(corner.varyings[6..9].iter()) {
+                *channel += c / 3.0;
+            }
+        }
+
+        uniforms.lighting.illuminate(&centroid, &normal, &uniforms.eye).shade(&Color::fromFloats(base[0], base[1], base[2]))
+    }
+}
+
+impl VertexShader for LitShader {
+    type Input = Vertex;
+    type Uniforms = LitUniforms;
+
+    fn shade(&self, input: &Vertex, uniforms: &LitUniforms) -> ClipVertex {
+        let p = &input.position;
+        let clip = uniforms.modelViewProjection * Vector4::new(p.x, p.y, p.z, 1.0);
+
+        let world = uniforms.toWorld(p);
+        let normal = uniforms.toWorldNormal(&input.normal);
+        let base = uniforms.color.modulate(&input.color);
+
+        let varyings = match uniforms.lighting.getShadingModel() {
+            ShadingModel::Phong | ShadingModel::Flat => {
+                let [r, g, b] = base.toFloats();
+                vec![world.x, world.y, world.z, normal.x, normal.y, normal.z, r, g, b]
+            },
+            ShadingModel::Gouraud => {
+                let shade = uniforms.lighting.illuminate(&world, &normal, &uniforms.eye).shade(&base);
+                let [r, g, b] = shade.color.toFloats();
+                vec![r, g, b, shade.intensity]
+            }
+        };
+
+        ClipVertex::new(clip, varyings)
+    }
+}
+
+impl FragmentShader for LitShader {
+    type Uniforms = LitUniforms;
+
+    fn shade(&self, fragment: &Fragment, uniforms: &LitUniforms) -> Option<Shade> {
+        let v = &fragment.varyings;
+
+        match uniforms.lighting.getShadingModel() {
+            ShadingModel::Phong | ShadingModel::Flat => {
+                let position = Vector3::new(v[0], v[1], v[2]);
+                let normal = Vector3::new(v[3], v[4], v[5]);
+                let base = Color::fromFloats(v[6], v[7], v[8]);
+
+                Some(uniforms.lighting.illuminate(&position, &normal, &uniforms.eye).shade(&base))
+            },
+            ShadingModel::Gouraud => {
+                Some(Shade::new(Color::fromFloats(v[0], v[1], v[2]), v[3]))
+            }
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod Light;
+pub mod Lighting;
+pub mod LitShader;
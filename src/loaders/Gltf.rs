@@ -0,0 +1,1109 @@
+use std::fs;
+use std::path::Path;
+
+use crate::vectors::{VectorProperties::*, Vector2::*, Vector3::*, Vector4::*};
+use crate::matrices::{MatrixProperties::*, Matrix44::*};
+use crate::quaternions::Quaternion::*;
+use crate::rasterizer::Color::*;
+use crate::mesh::{Mesh::*, Material::*};
+use crate::loaders::{LoadError::*, Json::*};
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_JSON: u32 = 0x4E4F_534A;
+const GLB_BIN: u32 = 0x004E_4942;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AnimationPath {
+    Translation,
+    Rotation,
+    Scale,
+    // Morph target weights, kept but not applied.
+    Weights
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Interpolation {
+    Linear,
+    Step,
+    CubicSpline
+}
+
+// A node's local transform is `matrix` when the file gives one, otherwise
+// translation * rotation * scale.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    pub children: Vec<usize>,
+    pub mesh: Option<usize>,
+    pub skin: Option<usize>,
+    pub matrix: Option<Matrix44>,
+    pub translation: Vector3,
+    pub rotation: Quaternion,
+    pub scale: Vector3
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfPrimitive {
+    pub mesh: Mesh,
+    pub material: Option<usize>,
+    // Skinning data parallel to the mesh vertices, empty when unskinned.
+    pub joints: Vec<[u16; 4]>,
+    pub weights: Vec<[f64; 4]>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>
+}
+
+// `material.diffuse` holds the base color factor and `material.texture` the
+// URI of an external base color image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMaterial {
+    pub material: Material,
+    pub baseColorFactor: [f64; 4],
+    pub baseColorTexture: Option<usize>
+}
+
+// Embedded images keep their encoded bytes; external ones only their URI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfImage {
+    pub name: Option<String>,
+    pub uri: Option<String>,
+    pub mimeType: Option<String>,
+    pub data: Option<Vec<u8>>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfSkin {
+    pub name: Option<String>,
+    pub joints: Vec<usize>,
+    pub inverseBindMatrices: Vec<Matrix44>,
+    pub skeleton: Option<usize>
+}
+
+// One animated property with its sampler resolved. Cubic spline keys store
+// in-tangent, value and out-tangent one after the other in `values`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfChannel {
+    pub node: usize,
+    pub path: AnimationPath,
+    pub interpolation: Interpolation,
+    pub times: Vec<f64>,
+    pub values: Vec<f64>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfAnimation {
+    pub name: Option<String>,
+    pub channels: Vec<GltfChannel>
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GltfModel {
+    pub meshes: Vec<GltfMesh>,
+    pub nodes: Vec<GltfNode>,
+    // Root nodes of each scene, and the scene to show by default.
+    pub scenes: Vec<Vec<usize>>,
+    pub scene: Option<usize>,
+    pub materials: Vec<GltfMaterial>,
+    pub images: Vec<GltfImage>,
+    pub skins: Vec<GltfSkin>,
+    pub animations: Vec<GltfAnimation>
+}
+
+pub struct GltfLoader;
+
+impl GltfLoader {
+    // Reads a .gltf or .glb file. Buffers may be embedded as base64 data URIs,
+    // held in the GLB binary chunk or stored next to the file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<GltfModel, LoadError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+
+        parseGltf(&bytes, Some(path.parent().unwrap_or_else(|| Path::new(""))))
+    }
+
+    // Parses .gltf text or a .glb container. Only embedded buffers can be
+    // resolved here since there is no directory to look in.
+    pub fn parse(bytes: &[u8]) -> Result<GltfModel, LoadError> {
+        parseGltf(bytes, None)
+    }
+}
+
+impl GltfNode {
+    pub fn getLocalMatrix(&self) -> Matrix44 {
+        if let Some(matrix) = self.matrix {
+            return matrix;
+        }
+
+        let mut local: Matrix44 = Default::default();
+        local.translate(&self.translation);
+        local *= self.rotation.toMatrix();
+        local.scale(&self.scale);
+        local
+    }
+}
+
+impl GltfChannel {
+    // Number of floats in one keyframe value.
+    pub fn getComponentCount(&self) -> usize {
+        let perKey = if self.interpolation == Interpolation::CubicSpline { 3 } else { 1 };
+
+        match self.path {
+            AnimationPath::Translation | AnimationPath::Scale => 3,
+            AnimationPath::Rotation => 4,
+            AnimationPath::Weights => self.values.len() / (self.times.len() * perKey).max(1)
+        }
+    }
+
+    // The value at `time`, held at the first or last key outside the keyed
+    // range. Rotations come back normalized. A channel without keys, or with
+    // fewer values than keys, has no value and gives an empty Vec.
+    pub fn sample(&self, time: f64) -> Vec<f64> {
+        let n = self.getComponentCount();
+        let cubic = self.interpolation == Interpolation::CubicSpline;
+
+        if self.times.is_empty() || self.values.len() < self.times.len() * n * if cubic { 3 } else { 1 } {
+            return Vec::new();
+        }
+
+        let value = |key: usize, part: usize| -> &[f64] {
+            let at = if cubic { (key * 3 + part) * n } else { key * n };
+            &self.values[at..at + n]
+        };
+
+        let last = self.times.len() - 1;
+
+        let key = match self.times.iter().rposition(|t| *t <= time) {
+            None => return value(0, 1).to_vec(),
+            Some(key) if key == last => return value(last, 1).to_vec(),
+            Some(key) => key
+        };
+
+        let dt = self.times[key + 1] - self.times[key];
+        let s = if dt > 0.0 { (time - self.times[key]) / dt } else { 0.0 };
+
+        let mut result: Vec<f64> = match self.interpolation {
+            Interpolation::Step => value(key, 1).to_vec(),
+            Interpolation::Linear if self.path == AnimationPath::Rotation => {
+                let a = value(key, 1);
+                let b = value(key + 1, 1);
+                let q = Quaternion::slerp(&Quaternion::new(a[0], a[1], a[2], a[3]), &Quaternion::new(b[0], b[1], b[2], b[3]), s);
+                vec![q.x, q.y, q.z, q.w]
+            },
+            Interpolation::Linear => {
+                value(key, 1).iter().zip(value(key + 1, 1).iter())
+                    .map(|(a, b)| a + (b - a) * s)
+                    .collect()
+            },
+            Interpolation::CubicSpline => {
+                // Hermite basis, with tangents scaled by the key interval.
+                let s2 = s * s;
+                let s3 = s2 * s;
+                let h00 = 2.0 * s3 - 3.0 * s2 + 1.0;
+                let h10 = s3 - 2.0 * s2 + s;
+                let h01 = -2.0 * s3 + 3.0 * s2;
+                let h11 = s3 - s2;
+
+                (0..n).map(|i| {
+                    h00 * value(key, 1)[i] + h10 * dt * value(key, 2)[i]
+                        + h01 * value(key + 1, 1)[i] + h11 * dt * value(key + 1, 0)[i]
+                }).collect()
+            }
+        };
+
+        if self.path == AnimationPath::Rotation {
+            let length = result.iter().map(|v| v * v).sum::<f64>().sqrt();
+
+            if length > 0.0 {
+                result.iter_mut().for_each(|v| *v /= length);
+            }
+        }
+
+        result
+    }
+}
+
+impl GltfAnimation {
+    pub fn getDuration(&self) -> f64 {
+        self.channels.iter()
+            .filter_map(|c| c.times.last())
+            .fold(0.0, |a, b| a.max(*b))
+    }
+
+    // Poses the nodes at `time`. Animated nodes drop any matrix they were
+    // given, as glTF only animates TRS nodes.
+    pub fn apply(&self, time: f64, nodes: &mut [GltfNode]) {
+        for channel in self.channels.iter() {
+            let node = match nodes.get_mut(channel.node) {
+                Some(node) => node,
+                None => continue
+            };
+
+            match (channel.path, channel.sample(time).as_slice()) {
+                (AnimationPath::Translation, [x, y, z]) => node.translation = Vector3::new(*x, *y, *z),
+                (AnimationPath::Rotation, [x, y, z, w]) => node.rotation = Quaternion::new(*x, *y, *z, *w),
+                (AnimationPath::Scale, [x, y, z]) => node.scale = Vector3::new(*x, *y, *z),
+                _ => continue
+            }
+
+            node.matrix = None;
+        }
+    }
+}
+
+impl GltfModel {
+    // World transform of every node, indexed like `nodes`.
+    pub fn getWorldMatrices(&self) -> Vec<Matrix44> {
+        let mut world: Vec<Matrix44> = vec![Default::default(); self.nodes.len()];
+        let mut stack: Vec<(usize, Matrix44)> = self.getRoots().into_iter()
+            .map(|root| (root, Default::default()))
+            .collect();
+
+        while let Some((index, parent)) = stack.pop() {
+            let node = &self.nodes[index];
+            world[index] = parent * node.getLocalMatrix();
+
+            for child in node.children.iter() {
+                stack.push((*child, world[index]));
+            }
+        }
+
+        world
+    }
+
+    // Joint matrices of a skin, each taking a bind-pose vertex to world space.
+    pub fn getJointMatrices(&self, skin: usize, world: &[Matrix44]) -> Vec<Matrix44> {
+        let skin = &self.skins[skin];
+
+        skin.joints.iter().enumerate()
+            .map(|(i, joint)| {
+                let inverseBind = skin.inverseBindMatrices.get(i).copied().unwrap_or_default();
+                world[*joint] * inverseBind
+            })
+            .collect()
+    }
+
+    // Every mesh in the default scene baked into one mesh in world space, in
+    // its rest pose.
+    pub fn toMesh(&self) -> Mesh {
+        let world = self.getWorldMatrices();
+        let mut baked = Mesh::default();
+        let mut stack: Vec<usize> = match self.scene.or(if self.scenes.is_empty() { None } else { Some(0) }) {
+            Some(scene) => self.scenes[scene].clone(),
+            None => self.getRoots()
+        };
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            stack.extend(node.children.iter());
+
+            let mesh = match node.mesh {
+                Some(mesh) => &self.meshes[mesh],
+                None => continue
+            };
+
+            let mut normalMatrix = world[index];
+            normalMatrix.invert();
+            normalMatrix.transpose();
+
+            for primitive in mesh.primitives.iter() {
+                let first = baked.getVertices().len() as u32;
+
+                for vertex in primitive.mesh.getVertices() {
+                    let p = world[index] * Vector4::new(vertex.position.x, vertex.position.y, vertex.position.z, 1.0);
+                    let n = normalMatrix * Vector4::new(vertex.normal.x, vertex.normal.y, vertex.normal.z, 0.0);
+                    let normal = Vector3::new(n.x, n.y, n.z);
+
+                    baked.addVertex(Vertex::new(
+                        Vector3::new(p.x, p.y, p.z),
+                        if Vector3::length(&normal) > 0.0 { Vector3::getNormalized(&normal) } else { normal },
+                        vertex.uv,
+                        vertex.color));
+                }
+
+                for [a, b, c] in primitive.mesh.triangles() {
+                    baked.addTriangle(first + a, first + b, first + c);
+                }
+            }
+        }
+
+        baked
+    }
+
+    // Nodes that are nobody's child.
+    fn getRoots(&self) -> Vec<usize> {
+        let mut isChild = vec![false; self.nodes.len()];
+
+        for node in self.nodes.iter() {
+            for child in node.children.iter() {
+                isChild[*child] = true;
+            }
+        }
+
+        (0..self.nodes.len()).filter(|i| !isChild[*i]).collect()
+    }
+}
+
+fn parseGltf(bytes: &[u8], directory: Option<&Path>) -> Result<GltfModel, LoadError> {
+    let (json, binary) = if bytes.len() >= 4 && readU32(bytes, 0) == GLB_MAGIC {
+        splitGlb(bytes)?
+    } else {
+        (bytes, None)
+    };
+
+    let text = std::str::from_utf8(json)
+        .map_err(|_| LoadError::Format("glTF JSON is not valid UTF-8".to_string()))?;
+
+    let document = Json::parse(text)?;
+
+    let version = document.get("asset")
+        .and_then(|asset| asset.get("version"))
+        .and_then(|v| v.asStr())
+        .ok_or_else(|| invalid("asset.version is missing"))?;
+
+    if !version.starts_with("2.") {
+        return Err(invalid(&format!("unsupported glTF version {}", version)));
+    }
+
+    if let Some(required) = list(&document, "extensionsRequired").first() {
+        return Err(invalid(&format!("required extension {} is not supported", required.asStr().unwrap_or("?"))));
+    }
+
+    let buffers = list(&document, "buffers").iter().enumerate()
+        .map(|(i, buffer)| loadBuffer(buffer, i, binary, directory))
+        .collect::<Result<Vec<Vec<u8>>, LoadError>>()?;
+
+    let gltf = Document {
+        json: &document,
+        buffers
+    };
+
+    let mut model = GltfModel::default();
+
+    for (i, image) in list(&document, "images").iter().enumerate() {
+        model.images.push(gltf.image(image, i)?);
+    }
+
+    let textures = list(&document, "textures");
+
+    for (i, material) in list(&document, "materials").iter().enumerate() {
+        model.materials.push(parseMaterial(material, i, textures, &model.images)?);
+    }
+
+    for (i, mesh) in list(&document, "meshes").iter().enumerate() {
+        model.meshes.push(gltf.mesh(mesh, i, &model.materials)?);
+    }
+
+    let nodeCount = list(&document, "nodes").len();
+    let skinCount = list(&document, "skins").len();
+
+    for (i, node) in list(&document, "nodes").iter().enumerate() {
+        model.nodes.push(parseNode(node, i, nodeCount, model.meshes.len(), skinCount)?);
+    }
+
+    checkHierarchy(&model.nodes)?;
+
+    for (i, scene) in list(&document, "scenes").iter().enumerate() {
+        let roots = indices(scene, "nodes", &format!("scenes[{}]", i), nodeCount)?;
+        model.scenes.push(roots);
+    }
+
+    model.scene = optionalIndex(&document, "scene", "scene", model.scenes.len())?;
+
+    for (i, skin) in list(&document, "skins").iter().enumerate() {
+        model.skins.push(gltf.skin(skin, i, nodeCount)?);
+    }
+
+    for (i, animation) in list(&document, "animations").iter().enumerate() {
+        model.animations.push(gltf.animation(animation, i, nodeCount)?);
+    }
+
+    Ok(model)
+}
+
+// Splits a GLB container into its JSON chunk and optional binary chunk.
+fn splitGlb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), LoadError> {
+    if bytes.len() < 12 {
+        return Err(invalid("GLB header is truncated"));
+    }
+
+    let version = readU32(bytes, 4);
+
+    if version != 2 {
+        return Err(invalid(&format!("unsupported GLB version {}", version)));
+    }
+
+    let length = (readU32(bytes, 8) as usize).min(bytes.len());
+    let mut offset = 12;
+    let mut json: Option<&[u8]> = None;
+    let mut binary: Option<&[u8]> = None;
+
+    while offset + 8 <= length {
+        let size = readU32(bytes, offset) as usize;
+        let kind = readU32(bytes, offset + 4);
+        let start = offset + 8;
+
+        if start + size > length {
+            return Err(invalid(&format!("GLB chunk at byte {} runs past the end of the file", offset)));
+        }
+
+        match kind {
+            GLB_JSON if json.is_none() => json = Some(&bytes[start..start + size]),
+            GLB_BIN if binary.is_none() => binary = Some(&bytes[start..start + size]),
+            _ => {}
+        }
+
+        offset = start + size;
+    }
+
+    let json = json.ok_or_else(|| invalid("GLB has no JSON chunk"))?;
+
+    Ok((json, binary))
+}
+
+fn loadBuffer(buffer: &Json, index: usize, binary: Option<&[u8]>, directory: Option<&Path>) -> Result<Vec<u8>, LoadError> {
+    let context = format!("buffers[{}]", index);
+    let length = buffer.get("byteLength").and_then(|l| l.asIndex())
+        .ok_or_else(|| invalid(&format!("{}: byteLength is missing", context)))?;
+
+    let data = match buffer.get("uri").and_then(|u| u.asStr()) {
+        Some(uri) if uri.starts_with("data:") => decodeDataUri(uri, &context)?,
+        Some(uri) => match directory {
+            Some(directory) if !uri.contains("://") => fs::read(directory.join(percentDecode(uri))).map_err(|e| {
+                invalid(&format!("{}: cannot read '{}': {}", context, uri, e))
+            })?,
+            _ => return Err(invalid(&format!("{}: external buffer '{}' cannot be loaded", context, uri)))
+        },
+        // Only the first buffer may live in the GLB binary chunk.
+        None if index == 0 && binary.is_some() => binary.unwrap().to_vec(),
+        None => return Err(invalid(&format!("{}: has no uri and there is no GLB binary chunk", context)))
+    };
+
+    if data.len() < length {
+        return Err(invalid(&format!("{}: holds {} bytes but byteLength is {}", context, data.len(), length)));
+    }
+
+    Ok(data)
+}
+
+fn decodeDataUri(uri: &str, context: &str) -> Result<Vec<u8>, LoadError> {
+    let comma = uri.find(',')
+        .ok_or_else(|| invalid(&format!("{}: malformed data URI", context)))?;
+
+    if !uri[..comma].ends_with(";base64") {
+        return Err(invalid(&format!("{}: only base64 data URIs are supported", context)));
+    }
+
+    decodeBase64(&uri[comma + 1..])
+        .ok_or_else(|| invalid(&format!("{}: invalid base64 data", context)))
+}
+
+fn decodeBase64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits: u32 = 0;
+    let mut count = 0;
+
+    for c in text.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            b' ' | b'\n' | b'\r' | b'\t' => continue,
+            _ => return None
+        };
+
+        bits = (bits << 6) | value as u32;
+        count += 6;
+
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+        }
+    }
+
+    Some(bytes)
+}
+
+// Undoes the %XX escapes allowed in relative URIs.
+fn percentDecode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            },
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parseMaterial(material: &Json, index: usize, textures: &[Json], images: &[GltfImage]) -> Result<GltfMaterial, LoadError> {
+    let context = format!("materials[{}]", index);
+    let name = material.get("name").and_then(|n| n.asStr()).map(|n| n.to_string())
+        .unwrap_or_else(|| format!("material{}", index));
+
+    let pbr = material.get("pbrMetallicRoughness");
+    let mut baseColorFactor = [1.0; 4];
+
+    if let Some(factor) = pbr.and_then(|p| p.get("baseColorFactor")) {
+        let values = numbers(factor, &format!("{}.baseColorFactor", context))?;
+
+        if values.len() != 4 {
+            return Err(invalid(&format!("{}: baseColorFactor needs 4 numbers", context)));
+        }
+
+        baseColorFactor.copy_from_slice(&values);
+    }
+
+    let baseColorTexture = match pbr.and_then(|p| p.get("baseColorTexture")) {
+        Some(info) => {
+            let texture = optionalIndex(info, "index", &context, textures.len())?
+                .ok_or_else(|| invalid(&format!("{}: baseColorTexture has no index", context)))?;
+
+            optionalIndex(&textures[texture], "source", &format!("textures[{}]", texture), images.len())?
+        },
+        None => None
+    };
+
+    let mut shared = Material::new(&name);
+    shared.diffuse = Color::fromFloats(baseColorFactor[0], baseColorFactor[1], baseColorFactor[2]);
+    shared.texture = baseColorTexture.and_then(|image| images[image].uri.clone());
+
+    Ok(GltfMaterial {
+        material: shared,
+        baseColorFactor,
+        baseColorTexture
+    })
+}
+
+fn parseNode(node: &Json, index: usize, nodeCount: usize, meshCount: usize, skinCount: usize) -> Result<GltfNode, LoadError> {
+    let context = format!("nodes[{}]", index);
+    let vector = |key: &str, default: Vector3| -> Result<Vector3, LoadError> {
+        match node.get(key) {
+            Some(value) => match numbers(value, &context)?.as_slice() {
+                [x, y, z] => Ok(Vector3::new(*x, *y, *z)),
+                _ => Err(invalid(&format!("{}: {} needs 3 numbers", context, key)))
+            },
+            None => Ok(default)
+        }
+    };
+
+    let rotation = match node.get("rotation") {
+        Some(value) => match numbers(value, &context)?.as_slice() {
+            [x, y, z, w] => Quaternion::new(*x, *y, *z, *w),
+            _ => return Err(invalid(&format!("{}: rotation needs 4 numbers", context)))
+        },
+        None => Default::default()
+    };
+
+    // glTF stores matrices column by column, the same order as Matrix44.
+    let matrix = match node.get("matrix") {
+        Some(value) => Some(toMatrix(&numbers(value, &context)?)
+            .ok_or_else(|| invalid(&format!("{}: matrix needs 16 numbers", context)))?),
+        None => None
+    };
+
+    Ok(GltfNode {
+        name: node.get("name").and_then(|n| n.asStr()).map(|n| n.to_string()),
+        children: indices(node, "children", &context, nodeCount)?,
+        mesh: optionalIndex(node, "mesh", &context, meshCount)?,
+        skin: optionalIndex(node, "skin", &context, skinCount)?,
+        matrix,
+        translation: vector("translation", Vector3::new(0.0, 0.0, 0.0))?,
+        rotation,
+        scale: vector("scale", Vector3::new(1.0, 1.0, 1.0))?
+    })
+}
+
+// Rejects parent links that would make the node hierarchy anything but a
+// set of disjoint trees.
+fn checkHierarchy(nodes: &[GltfNode]) -> Result<(), LoadError> {
+    let mut parent: Vec<Option<usize>> = vec![None; nodes.len()];
+
+    for (index, node) in nodes.iter().enumerate() {
+        for child in node.children.iter() {
+            if parent[*child].is_some() || *child == index {
+                return Err(invalid(&format!("nodes[{}]: node {} has more than one parent", index, child)));
+            }
+
+            parent[*child] = Some(index);
+        }
+    }
+
+    // With single parents a cycle is a loop of nodes unreachable from a root.
+    let mut reached = vec![false; nodes.len()];
+    let mut stack: Vec<usize> = (0..nodes.len()).filter(|i| parent[*i].is_none()).collect();
+
+    while let Some(index) = stack.pop() {
+        reached[index] = true;
+        stack.extend(nodes[index].children.iter());
+    }
+
+    match reached.iter().position(|r| !r) {
+        Some(index) => Err(invalid(&format!("nodes[{}]: node hierarchy has a cycle", index))),
+        None => Ok(())
+    }
+}
+
+// The elements of one accessor as floats, `components` per element.
+struct Accessor {
+    values: Vec<f64>,
+    components: usize,
+    count: usize,
+    componentType: usize
+}
+
+impl Accessor {
+    fn element(&self, index: usize) -> &[f64] {
+        &self.values[index * self.components..(index + 1) * self.components]
+    }
+}
+
+struct Document<'a> {
+    json: &'a Json,
+    buffers: Vec<Vec<u8>>
+}
+
+impl<'a> Document<'a> {
+    // Reads accessor `index`, which must have one of the `types` given.
+    // Normalized integers become floats in 0..1, or -1..1 when signed.
+    fn accessor(&self, index: usize, types: &[&str]) -> Result<Accessor, LoadError> {
+        let context = format!("accessors[{}]", index);
+        let accessor = list(self.json, "accessors").get(index)
+            .ok_or_else(|| invalid(&format!("{}: accessor does not exist", context)))?;
+
+        let kind = accessor.get("type").and_then(|t| t.asStr()).unwrap_or("");
+
+        if !types.contains(&kind) {
+            return Err(invalid(&format!("{}: type '{}' should be one of {}", context, kind, types.join(", "))));
+        }
+
+        let components = match kind {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            "VEC4" | "MAT2" => 4,
+            "MAT3" => 9,
+            _ => 16
+        };
+
+        let componentType = accessor.get("componentType").and_then(|c| c.asIndex()).unwrap_or(0);
+        let size = match componentType {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            other => return Err(invalid(&format!("{}: unknown componentType {}", context, other)))
+        };
+
+        let count = accessor.get("count").and_then(|c| c.asIndex())
+            .ok_or_else(|| invalid(&format!("{}: count is missing", context)))?;
+        let normalized = accessor.get("normalized").and_then(|n| n.asBool()).unwrap_or(false);
+
+        if accessor.get("sparse").is_some() {
+            return Err(invalid(&format!("{}: sparse accessors are not supported", context)));
+        }
+
+        let elementSize = size * components;
+        let tooMany = || invalid(&format!("{}: {} elements do not fit in the buffers", context, count));
+
+        // Without a buffer view every element is zero. Such an accessor is
+        // still limited to what the buffers could have held, so a bogus
+        // count cannot ask for an arbitrary allocation.
+        let view = match optionalIndex(accessor, "bufferView", &context, list(self.json, "bufferViews").len())? {
+            Some(view) => view,
+            None => {
+                let available: usize = self.buffers.iter().map(|b| b.len()).sum();
+
+                return match count.checked_mul(elementSize) {
+                    Some(length) if length <= available => Ok(Accessor {
+                        values: vec![0.0; count * components],
+                        components,
+                        count,
+                        componentType
+                    }),
+                    _ => Err(tooMany())
+                };
+            }
+        };
+
+        let (bytes, stride) = self.bufferView(view)?;
+        let stride = stride.unwrap_or(elementSize);
+        let start = accessor.get("byteOffset").and_then(|o| o.asIndex()).unwrap_or(0);
+
+        if stride < elementSize {
+            return Err(invalid(&format!("{}: byteStride {} of bufferViews[{}] is smaller than an element", context, stride, view)));
+        }
+
+        // Offset of the end of the last element, which must lie in the view.
+        let end = match count.checked_sub(1) {
+            Some(last) => stride.checked_mul(last)
+                .and_then(|offset| offset.checked_add(start))
+                .and_then(|offset| offset.checked_add(elementSize)),
+            None => Some(start)
+        };
+
+        match end {
+            Some(end) if end <= bytes.len() => {},
+            _ => return Err(invalid(&format!("{}: {} elements from byteOffset {} do not fit in bufferViews[{}]", context, count, start, view)))
+        }
+
+        let mut values: Vec<f64> = Vec::with_capacity(count * components);
+
+        for element in 0..count {
+            for component in 0..components {
+                let b = &bytes[start + element * stride + component * size..];
+
+                values.push(match (componentType, normalized) {
+                    (5120, false) => b[0] as i8 as f64,
+                    (5120, true) => (b[0] as i8 as f64 / 127.0).max(-1.0),
+                    (5121, false) => b[0] as f64,
+                    (5121, true) => b[0] as f64 / 255.0,
+                    (5122, false) => i16::from_le_bytes([b[0], b[1]]) as f64,
+                    (5122, true) => (i16::from_le_bytes([b[0], b[1]]) as f64 / 32767.0).max(-1.0),
+                    (5123, false) => u16::from_le_bytes([b[0], b[1]]) as f64,
+                    (5123, true) => u16::from_le_bytes([b[0], b[1]]) as f64 / 65535.0,
+                    (5125, _) => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                    _ => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64
+                });
+            }
+        }
+
+        Ok(Accessor {
+            values,
+            components,
+            count,
+            componentType
+        })
+    }
+
+    fn bufferView(&self, index: usize) -> Result<(&[u8], Option<usize>), LoadError> {
+        let context = format!("bufferViews[{}]", index);
+        let view = &list(self.json, "bufferViews")[index];
+
+        let buffer = optionalIndex(view, "buffer", &context, self.buffers.len())?
+            .ok_or_else(|| invalid(&format!("{}: buffer is missing", context)))?;
+        let length = view.get("byteLength").and_then(|l| l.asIndex())
+            .ok_or_else(|| invalid(&format!("{}: byteLength is missing", context)))?;
+        let offset = view.get("byteOffset").and_then(|o| o.asIndex()).unwrap_or(0);
+        let stride = view.get("byteStride").and_then(|s| s.asIndex());
+
+        let bytes = offset.checked_add(length)
+            .and_then(|end| self.buffers[buffer].get(offset..end))
+            .ok_or_else(|| invalid(&format!("{}: runs past the end of buffers[{}]", context, buffer)))?;
+
+        Ok((bytes, stride))
+    }
+
+    fn optionalAccessor(&self, json: &Json, key: &str, context: &str, types: &[&str]) -> Result<Option<Accessor>, LoadError> {
+        match optionalIndex(json, key, context, list(self.json, "accessors").len())? {
+            Some(index) => self.accessor(index, types).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    fn image(&self, image: &Json, index: usize) -> Result<GltfImage, LoadError> {
+        let context = format!("images[{}]", index);
+        let text = |key: &str| image.get(key).and_then(|v| v.asStr()).map(|v| v.to_string());
+        let mut uri = text("uri");
+
+        let data = match (&uri, optionalIndex(image, "bufferView", &context, list(self.json, "bufferViews").len())?) {
+            (Some(u), _) if u.starts_with("data:") => Some(decodeDataUri(u, &context)?),
+            (_, Some(view)) => Some(self.bufferView(view)?.0.to_vec()),
+            _ => None
+        };
+
+        // A data URI is kept as bytes, not as a path.
+        if data.is_some() {
+            uri = None;
+        }
+
+        Ok(GltfImage {
+            name: text("name"),
+            uri,
+            mimeType: text("mimeType"),
+            data
+        })
+    }
+
+    fn mesh(&self, mesh: &Json, index: usize, materials: &[GltfMaterial]) -> Result<GltfMesh, LoadError> {
+        let mut primitives: Vec<GltfPrimitive> = Vec::new();
+
+        for (p, primitive) in list(mesh, "primitives").iter().enumerate() {
+            let context = format!("meshes[{}].primitives[{}]", index, p);
+            let attributes = primitive.get("attributes")
+                .ok_or_else(|| invalid(&format!("{}: attributes are missing", context)))?;
+
+            let positions = self.optionalAccessor(attributes, "POSITION", &context, &["VEC3"])?
+                .ok_or_else(|| invalid(&format!("{}: POSITION is missing", context)))?;
+            let count = positions.count;
+
+            let attribute = |name: &str, types: &[&str]| -> Result<Option<Accessor>, LoadError> {
+                match self.optionalAccessor(attributes, name, &context, types)? {
+                    Some(accessor) if accessor.count != count => Err(invalid(&format!(
+                        "{}: {} has {} elements but POSITION has {}", context, name, accessor.count, count))),
+                    other => Ok(other)
+                }
+            };
+
+            let normals = attribute("NORMAL", &["VEC3"])?;
+            let uvs = attribute("TEXCOORD_0", &["VEC2"])?;
+            let colors = attribute("COLOR_0", &["VEC3", "VEC4"])?;
+            let joints = attribute("JOINTS_0", &["VEC4"])?;
+            let weights = attribute("WEIGHTS_0", &["VEC4"])?;
+
+            let material = optionalIndex(primitive, "material", &context, materials.len())?;
+            let base = material.map(|m| materials[m].material.diffuse).unwrap_or(Color::WHITE);
+
+            let mut built = Mesh::default();
+
+            for i in 0..count {
+                let p = positions.element(i);
+                let normal = normals.as_ref().map(|n| n.element(i))
+                    .map(|n| Vector3::new(n[0], n[1], n[2]))
+                    .unwrap_or_else(|| Vector3::new(0.0, 0.0, 0.0));
+                let uv = uvs.as_ref().map(|t| t.element(i))
+                    .map(|t| Vector2::new(t[0], t[1]))
+                    .unwrap_or_else(|| Vector2::new(0.0, 0.0));
+                let color = match colors.as_ref().map(|c| c.element(i)) {
+                    Some(c) => base.modulate(&Color::fromFloats(c[0], c[1], c[2])),
+                    None => base
+                };
+
+                built.addVertex(Vertex::new(Vector3::new(p[0], p[1], p[2]), normal, uv, color));
+            }
+
+            let order: Vec<u32> = match self.optionalAccessor(primitive, "indices", &context, &["SCALAR"])? {
+                Some(indices) => {
+                    // Unsigned byte, short or int.
+                    if ![5121, 5123, 5125].contains(&indices.componentType) {
+                        return Err(invalid(&format!("{}: indices must be unsigned integers, not componentType {}", context, indices.componentType)));
+                    }
+
+                    if let Some(bad) = indices.values.iter().find(|i| **i as usize >= count) {
+                        return Err(invalid(&format!("{}: index {} out of range, {} vertices", context, bad, count)));
+                    }
+
+                    indices.values.iter().map(|i| *i as u32).collect()
+                },
+                None => (0..count as u32).collect()
+            };
+
+            match primitive.get("mode").and_then(|m| m.asIndex()).unwrap_or(4) {
+                4 => {
+                    if !order.len().is_multiple_of(3) {
+                        return Err(invalid(&format!("{}: {} indices do not make whole triangles", context, order.len())));
+                    }
+
+                    for t in order.chunks_exact(3) {
+                        built.addTriangle(t[0], t[1], t[2]);
+                    }
+                },
+                // Strips flip every other triangle to keep the winding.
+                5 => {
+                    for i in 0..order.len().saturating_sub(2) {
+                        if i % 2 == 0 {
+                            built.addTriangle(order[i], order[i + 1], order[i + 2]);
+                        } else {
+                            built.addTriangle(order[i], order[i + 2], order[i + 1]);
+                        }
+                    }
+                },
+                6 => {
+                    for i in 1..order.len().saturating_sub(1) {
+                        built.addTriangle(order[0], order[i], order[i + 1]);
+                    }
+                },
+                mode => return Err(invalid(&format!("{}: primitive mode {} is not supported, only triangles", context, mode)))
+            }
+
+            primitives.push(GltfPrimitive {
+                mesh: built,
+                material,
+                joints: joints.map(|j| {
+                    (0..count).map(|i| {
+                        let e = j.element(i);
+                        [e[0] as u16, e[1] as u16, e[2] as u16, e[3] as u16]
+                    }).collect()
+                }).unwrap_or_default(),
+                weights: weights.map(|w| {
+                    (0..count).map(|i| {
+                        let e = w.element(i);
+                        [e[0], e[1], e[2], e[3]]
+                    }).collect()
+                }).unwrap_or_default()
+            });
+        }
+
+        Ok(GltfMesh {
+            name: mesh.get("name").and_then(|n| n.asStr()).map(|n| n.to_string()),
+            primitives
+        })
+    }
+
+    fn skin(&self, skin: &Json, index: usize, nodeCount: usize) -> Result<GltfSkin, LoadError> {
+        let context = format!("skins[{}]", index);
+        let joints = indices(skin, "joints", &context, nodeCount)?;
+
+        let inverseBindMatrices: Vec<Matrix44> = match self.optionalAccessor(skin, "inverseBindMatrices", &context, &["MAT4"])? {
+            Some(matrices) => {
+                if matrices.count < joints.len() {
+                    return Err(invalid(&format!("{}: {} inverse bind matrices for {} joints", context, matrices.count, joints.len())));
+                }
+
+                matrices.values.chunks_exact(16).filter_map(toMatrix).collect()
+            },
+            None => Vec::new()
+        };
+
+        Ok(GltfSkin {
+            name: skin.get("name").and_then(|n| n.asStr()).map(|n| n.to_string()),
+            joints,
+            inverseBindMatrices,
+            skeleton: optionalIndex(skin, "skeleton", &context, nodeCount)?
+        })
+    }
+
+    fn animation(&self, animation: &Json, index: usize, nodeCount: usize) -> Result<GltfAnimation, LoadError> {
+        let samplers = list(animation, "samplers");
+        let mut channels: Vec<GltfChannel> = Vec::new();
+
+        for (c, channel) in list(animation, "channels").iter().enumerate() {
+            let context = format!("animations[{}].channels[{}]", index, c);
+            let target = channel.get("target")
+                .ok_or_else(|| invalid(&format!("{}: target is missing", context)))?;
+
+            // Targets without a node belong to extensions.
+            let node = match optionalIndex(target, "node", &context, nodeCount)? {
+                Some(node) => node,
+                None => continue
+            };
+
+            let path = match target.get("path").and_then(|p| p.asStr()) {
+                Some("translation") => AnimationPath::Translation,
+                Some("rotation") => AnimationPath::Rotation,
+                Some("scale") => AnimationPath::Scale,
+                Some("weights") => AnimationPath::Weights,
+                _ => continue
+            };
+
+            let sampler = optionalIndex(channel, "sampler", &context, samplers.len())?
+                .map(|s| &samplers[s])
+                .ok_or_else(|| invalid(&format!("{}: sampler is missing", context)))?;
+
+            let interpolation = match sampler.get("interpolation").and_then(|i| i.asStr()).unwrap_or("LINEAR") {
+                "LINEAR" => Interpolation::Linear,
+                "STEP" => Interpolation::Step,
+                "CUBICSPLINE" => Interpolation::CubicSpline,
+                other => return Err(invalid(&format!("{}: unknown interpolation '{}'", context, other)))
+            };
+
+            let times = self.optionalAccessor(sampler, "input", &context, &["SCALAR"])?
+                .ok_or_else(|| invalid(&format!("{}: sampler input is missing", context)))?
+                .values;
+            let values = self.optionalAccessor(sampler, "output", &context, &["SCALAR", "VEC3", "VEC4"])?
+                .ok_or_else(|| invalid(&format!("{}: sampler output is missing", context)))?
+                .values;
+
+            if times.is_empty() || times.windows(2).any(|w| w[1] < w[0]) {
+                return Err(invalid(&format!("{}: key times must be present and increasing", context)));
+            }
+
+            let perKey = if interpolation == Interpolation::CubicSpline { 3 } else { 1 };
+            let components = match path {
+                AnimationPath::Translation | AnimationPath::Scale => 3,
+                AnimationPath::Rotation => 4,
+                AnimationPath::Weights => (values.len() / (times.len() * perKey)).max(1)
+            };
+
+            if values.len() != times.len() * perKey * components {
+                return Err(invalid(&format!("{}: {} output values do not match {} keys", context, values.len(), times.len())));
+            }
+
+            channels.push(GltfChannel {
+                node,
+                path,
+                interpolation,
+                times,
+                values
+            });
+        }
+
+        Ok(GltfAnimation {
+            name: animation.get("name").and_then(|n| n.asStr()).map(|n| n.to_string()),
+            channels
+        })
+    }
+}
+
+fn invalid(message: &str) -> LoadError {
+    LoadError::Format(message.to_string())
+}
+
+fn list<'a>(json: &'a Json, key: &str) -> &'a [Json] {
+    json.get(key).and_then(|v| v.asArray()).unwrap_or(&[])
+}
+
+fn optionalIndex(json: &Json, key: &str, context: &str, count: usize) -> Result<Option<usize>, LoadError> {
+    match json.get(key) {
+        None => Ok(None),
+        Some(value) => match value.asIndex() {
+            Some(index) if index < count => Ok(Some(index)),
+            _ => Err(invalid(&format!("{}: {} {:?} is not a valid index, {} defined", context, key, value, count)))
+        }
+    }
+}
+
+fn indices(json: &Json, key: &str, context: &str, count: usize) -> Result<Vec<usize>, LoadError> {
+    list(json, key).iter()
+        .map(|value| match value.asIndex() {
+            Some(index) if index < count => Ok(index),
+            _ => Err(invalid(&format!("{}: {} {:?} is not a valid index, {} defined", context, key, value, count)))
+        })
+        .collect()
+}
+
+fn numbers(value: &Json, context: &str) -> Result<Vec<f64>, LoadError> {
+    value.asArray()
+        .ok_or_else(|| invalid(&format!("{}: expected an array of numbers", context)))?
+        .iter()
+        .map(|v| v.asNumber().ok_or_else(|| invalid(&format!("{}: expected an array of numbers", context))))
+        .collect()
+}
+
+fn toMatrix(values: &[f64]) -> Option<Matrix44> {
+    if values.len() != 16 {
+        return None;
+    }
+
+    let v = values;
+
+    Some(Matrix44 {
+        m00: v[0], m01: v[1], m02: v[2], m03: v[3],
+        m10: v[4], m11: v[5], m12: v[6], m13: v[7],
+        m20: v[8], m21: v[9], m22: v[10], m23: v[11],
+        m30: v[12], m31: v[13], m32: v[14], m33: v[15]
+    })
+}
+
+fn readU32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use crate::loaders::LoadError::*;
+
+// Just enough JSON for the glTF loader. Objects keep the last value given
+// for a repeated key.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(HashMap<String, Json>)
+}
+
+impl Json {
+    pub fn parse(source: &str) -> Result<Json, LoadError> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            position: 0,
+            line: 1,
+            depth: 0
+        };
+
+        let value = parser.value()?;
+        parser.whitespace();
+
+        if parser.position < parser.chars.len() {
+            return Err(parser.error("unexpected text after the JSON value"));
+        }
+
+        Ok(value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.get(key),
+            _ => None
+        }
+    }
+
+    pub fn asNumber(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None
+        }
+    }
+
+    // Non-negative whole numbers only, as used for indices and counts.
+    pub fn asIndex(&self) -> Option<usize> {
+        self.asNumber()
+            .filter(|n| *n >= 0.0 && n.fract() == 0.0 && *n <= usize::MAX as f64)
+            .map(|n| n as usize)
+    }
+
+    pub fn asBool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None
+        }
+    }
+
+    pub fn asStr(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None
+        }
+    }
+
+    pub fn asArray(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None
+        }
+    }
+}
+
+// Arrays and objects nested deeper than this are rejected rather than
+// recursing until the stack runs out.
+const MAX_DEPTH: usize = 128;
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+    depth: usize
+}
+
+impl Parser {
+    fn error<S: Into<String>>(&self, message: S) -> LoadError {
+        LoadError::parse(self.line, message)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+
+        if c == '\n' {
+            self.line += 1;
+        }
+
+        Some(c)
+    }
+
+    fn whitespace(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), LoadError> {
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(format!("expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(format!("expected '{}', found the end of the input", expected)))
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Json) -> Result<Json, LoadError> {
+        for expected in word.chars() {
+            if self.bump() != Some(expected) {
+                return Err(self.error(format!("invalid literal, expected '{}'", word)));
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, LoadError> {
+        self.whitespace();
+
+        match self.peek() {
+            Some('{') => self.nested(Parser::object),
+            Some('[') => self.nested(Parser::array),
+            Some('"') => self.string().map(Json::String),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('n') => self.keyword("null", Json::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(self.error(format!("unexpected '{}'", c))),
+            None => Err(self.error("unexpected end of the input"))
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Parser) -> Result<Json, LoadError>) -> Result<Json, LoadError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(format!("nested deeper than {} levels", MAX_DEPTH)));
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Result<Json, LoadError> {
+        let mut members: HashMap<String, Json> = HashMap::new();
+        self.expect('{')?;
+        self.whitespace();
+
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(Json::Object(members));
+        }
+
+        loop {
+            self.whitespace();
+
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a member name"));
+            }
+
+            let key = self.string()?;
+            self.whitespace();
+            self.expect(':')?;
+            let value = self.value()?;
+            members.insert(key, value);
+            self.whitespace();
+
+            match self.bump() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(members)),
+                _ => return Err(self.error("expected ',' or '}' in object"))
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, LoadError> {
+        let mut items: Vec<Json> = Vec::new();
+        self.expect('[')?;
+        self.whitespace();
+
+        if self.peek() == Some(']') {
+            self.bump();
+            return Ok(Json::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+            self.whitespace();
+
+            match self.bump() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(items)),
+                _ => return Err(self.error("expected ',' or ']' in array"))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, LoadError> {
+        let mut text = String::new();
+        self.expect('"')?;
+
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(text),
+                Some('\\') => {
+                    let escaped = match self.bump() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicodeEscape()?,
+                        _ => return Err(self.error("invalid escape in string"))
+                    };
+
+                    text.push(escaped);
+                },
+                Some('\n') | None => return Err(self.error("unterminated string")),
+                Some(c) => text.push(c)
+            }
+        }
+    }
+
+    // The four hex digits after \u, combining surrogate pairs.
+    fn unicodeEscape(&mut self) -> Result<char, LoadError> {
+        let high = self.hex4()?;
+
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.bump() != Some('\\') || self.bump() != Some('u') {
+                return Err(self.error("unpaired surrogate in string"));
+            }
+
+            let low = self.hex4()?;
+
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate in string"));
+            }
+
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        std::char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, LoadError> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            let digit = self.bump()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("invalid unicode escape"))?;
+
+            code = code * 16 + digit;
+        }
+
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Json, LoadError> {
+        let start = self.position;
+
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' || c == 'e' || c == 'E' {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+
+        let text: String = self.chars[start..self.position].iter().collect();
+
+        text.parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(Json::Number)
+            .ok_or_else(|| self.error(format!("invalid number '{}'", text)))
+    }
+}
@@ -0,0 +1,44 @@
+use std::{error, fmt, io};
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    // Malformed input at a 1-based line of a text format.
+    Parse { line: usize, message: String },
+    // Malformed input with no line to point at, e.g. in binary data.
+    Format(String)
+}
+
+impl LoadError {
+    pub fn parse<S: Into<String>>(line: usize, message: S) -> LoadError {
+        LoadError::Parse {
+            line,
+            message: message.into()
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "{}", e),
+            LoadError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            LoadError::Format(message) => write!(f, "{}", message)
+        }
+    }
+}
+
+impl error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            LoadError::Io(e) => Some(e),
+            LoadError::Parse { .. } | LoadError::Format(_) => None
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> LoadError {
+        LoadError::Io(e)
+    }
+}
@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::vectors::{Vector2::*, Vector3::*};
+use crate::rasterizer::Color::*;
+use crate::mesh::{Mesh::*, Material::*};
+use crate::loaders::LoadError::*;
+
+// A run of the index buffer that shares one object, group and material.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjGroup {
+    pub object: Option<String>,
+    pub name: Option<String>,
+    pub material: Option<String>,
+    pub start: usize,
+    pub count: usize
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjModel {
+    pub mesh: Mesh,
+    pub groups: Vec<ObjGroup>,
+    pub materials: HashMap<String, Material>
+}
+
+// Indices into the position, texture coordinate and normal lists of one
+// face corner, already resolved to be 0-based.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>
+}
+
+pub struct ObjLoader;
+
+impl ObjLoader {
+    // Reads an OBJ file along with the material libraries it names, which are
+    // looked up relative to the OBJ file. A library that is missing or cannot
+    // be read adds no materials, as the model is still usable untinted.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ObjModel, LoadError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+        let mut materials: HashMap<String, Material> = HashMap::new();
+
+        for line in source.lines() {
+            let mut tokens = stripComment(line).split_whitespace();
+
+            if tokens.next() != Some("mtllib") {
+                continue;
+            }
+
+            for library in tokens {
+                let text = match fs::read_to_string(directory.join(library)) {
+                    Ok(text) => text,
+                    Err(_) => continue
+                };
+
+                let parsed = ObjLoader::parseMaterials(&text).map_err(|e| match e {
+                    LoadError::Parse { line, message } => {
+                        LoadError::parse(line, format!("in material library '{}': {}", library, message))
+                    },
+                    other => other
+                })?;
+
+                materials.extend(parsed);
+            }
+        }
+
+        ObjLoader::parse(&source, &materials)
+    }
+
+    // Parses OBJ source. Faces with more than three corners are split into a
+    // fan around their first corner, and each vertex takes the diffuse color
+    // of the material in use. Statements the engine has no use for, such as
+    // smoothing groups and lines, are skipped.
+    pub fn parse(source: &str, materials: &HashMap<String, Material>) -> Result<ObjModel, LoadError> {
+        let mut positions: Vec<Vector3> = Vec::new();
+        let mut colors: Vec<Option<Color>> = Vec::new();
+        let mut uvs: Vec<Vector2> = Vec::new();
+        let mut normals: Vec<Vector3> = Vec::new();
+
+        let mut model = ObjModel {
+            materials: materials.clone(),
+            ..Default::default()
+        };
+
+        let mut object: Option<String> = None;
+        let mut group: Option<String> = None;
+        let mut material: Option<String> = None;
+
+        // Each distinct corner/material pair becomes one mesh vertex.
+        let mut vertexIndex: HashMap<(Corner, Option<String>), u32> = HashMap::new();
+
+        for (number, line) in source.lines().enumerate() {
+            let number = number + 1;
+            let mut tokens = stripComment(line).split_whitespace();
+
+            let keyword = match tokens.next() {
+                Some(keyword) => keyword,
+                None => continue
+            };
+
+            let args: Vec<&str> = tokens.collect();
+
+            match keyword {
+                "v" => {
+                    // x y z, an optional w, then an optional RGB color.
+                    if args.len() == 5 {
+                        return Err(LoadError::parse(number, "vertex position needs 3, 4, 6 or 7 numbers, found 5"));
+                    }
+
+                    let values = parseFloats(number, &args, 3, 7, "vertex position")?;
+                    positions.push(Vector3::new(values[0], values[1], values[2]));
+
+                    // Some exporters append an RGB color to each position.
+                    colors.push(if values.len() >= 6 {
+                        let c = &values[values.len() - 3..];
+                        Some(Color::fromFloats(c[0], c[1], c[2]))
+                    } else {
+                        None
+                    });
+                },
+                "vt" => {
+                    let values = parseFloats(number, &args, 1, 3, "texture coordinate")?;
+                    uvs.push(Vector2::new(values[0], *values.get(1).unwrap_or(&0.0)));
+                },
+                "vn" => {
+                    let values = parseFloats(number, &args, 3, 3, "normal")?;
+                    normals.push(Vector3::new(values[0], values[1], values[2]));
+                },
+                "f" => {
+                    if args.len() < 3 {
+                        return Err(LoadError::parse(number, format!("face needs at least 3 vertices, found {}", args.len())));
+                    }
+
+                    let mut corners: Vec<u32> = Vec::with_capacity(args.len());
+
+                    for arg in args.iter() {
+                        let corner = parseCorner(number, arg, positions.len(), uvs.len(), normals.len())?;
+                        let key = (corner, material.clone());
+
+                        let index = match vertexIndex.get(&key) {
+                            Some(index) => *index,
+                            None => {
+                                let diffuse = material.as_ref()
+                                    .and_then(|name| model.materials.get(name))
+                                    .map(|m| m.diffuse);
+
+                                let index = model.mesh.addVertex(Vertex::new(
+                                    positions[corner.position],
+                                    corner.normal.map(|n| normals[n]).unwrap_or_else(|| Vector3::new(0.0, 0.0, 0.0)),
+                                    corner.uv.map(|t| uvs[t]).unwrap_or_else(|| Vector2::new(0.0, 0.0)),
+                                    diffuse.or(colors[corner.position]).unwrap_or(Color::WHITE)));
+
+                                vertexIndex.insert(key, index);
+                                index
+                            }
+                        };
+
+                        corners.push(index);
+                    }
+
+                    let start = model.mesh.getIndices().len();
+
+                    for k in 1..corners.len() - 1 {
+                        model.mesh.addTriangle(corners[0], corners[k], corners[k + 1]);
+                    }
+
+                    let count = model.mesh.getIndices().len() - start;
+
+                    match model.groups.last_mut() {
+                        Some(last) if last.object == object && last.name == group && last.material == material => {
+                            last.count += count;
+                        },
+                        _ => model.groups.push(ObjGroup {
+                            object: object.clone(),
+                            name: group.clone(),
+                            material: material.clone(),
+                            start,
+                            count
+                        })
+                    }
+                },
+                "o" => {
+                    object = joinName(&args);
+                    group = None;
+                },
+                "g" => group = joinName(&args),
+                "usemtl" => {
+                    let name = joinName(&args);
+
+                    if name.is_none() {
+                        return Err(LoadError::parse(number, "usemtl needs a material name"));
+                    }
+
+                    material = name;
+                },
+                "mtllib" if args.is_empty() => {
+                    return Err(LoadError::parse(number, "mtllib needs a file name"));
+                },
+                _ => {}
+            }
+        }
+
+        Ok(model)
+    }
+
+    // Parses an MTL material library, keeping each material's diffuse color
+    // (Kd) and diffuse texture (map_Kd).
+    pub fn parseMaterials(source: &str) -> Result<HashMap<String, Material>, LoadError> {
+        let mut materials: HashMap<String, Material> = HashMap::new();
+        let mut current: Option<Material> = None;
+
+        for (number, line) in source.lines().enumerate() {
+            let number = number + 1;
+            let mut tokens = stripComment(line).split_whitespace();
+
+            let keyword = match tokens.next() {
+                Some(keyword) => keyword,
+                None => continue
+            };
+
+            let args: Vec<&str> = tokens.collect();
+
+            match keyword {
+                "newmtl" => {
+                    let name = joinName(&args)
+                        .ok_or_else(|| LoadError::parse(number, "newmtl needs a material name"))?;
+
+                    if let Some(done) = current.take() {
+                        materials.insert(done.name.clone(), done);
+                    }
+
+                    current = Some(Material::new(&name));
+                },
+                "Kd" | "map_Kd" => {
+                    let material = current.as_mut()
+                        .ok_or_else(|| LoadError::parse(number, format!("{} before any newmtl", keyword)))?;
+
+                    if keyword == "Kd" {
+                        let values = parseFloats(number, &args, 3, 3, "diffuse color")?;
+                        material.diffuse = Color::fromFloats(values[0], values[1], values[2]);
+                    } else {
+                        // Options such as -s come first; the path is last.
+                        let path = args.last()
+                            .ok_or_else(|| LoadError::parse(number, "map_Kd needs a texture path"))?;
+                        material.texture = Some(path.to_string());
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        if let Some(done) = current {
+            materials.insert(done.name.clone(), done);
+        }
+
+        Ok(materials)
+    }
+}
+
+fn stripComment(line: &str) -> &str {
+    match line.find('#') {
+        Some(comment) => &line[..comment],
+        None => line
+    }
+}
+
+fn joinName(args: &[&str]) -> Option<String> {
+    if args.is_empty() {
+        None
+    } else {
+        Some(args.join(" "))
+    }
+}
+
+fn parseFloats(line: usize, args: &[&str], min: usize, max: usize, what: &str) -> Result<Vec<f64>, LoadError> {
+    if args.len() < min || args.len() > max {
+        let expected = if min == max { format!("{}", min) } else { format!("{} to {}", min, max) };
+        return Err(LoadError::parse(line, format!("{} needs {} numbers, found {}", what, expected, args.len())));
+    }
+
+    args.iter()
+        .map(|arg| {
+            arg.parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| LoadError::parse(line, format!("invalid number '{}' in {}", arg, what)))
+        })
+        .collect()
+}
+
+// Resolves a 1-based or negative (counted back from the latest) OBJ index
+// against a list of `count` elements.
+fn resolveIndex(line: usize, text: &str, count: usize, what: &str) -> Result<usize, LoadError> {
+    let index: i64 = text.parse()
+        .map_err(|_| LoadError::parse(line, format!("invalid {} index '{}'", what, text)))?;
+
+    let resolved: i64 = if index < 0 { count as i64 + index } else { index - 1 };
+
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(LoadError::parse(line, format!("{} index {} out of range, {} defined so far", what, index, count)));
+    }
+
+    Ok(resolved as usize)
+}
+
+// Parses one face corner: v, v/vt, v//vn or v/vt/vn.
+fn parseCorner(line: usize, text: &str, positions: usize, uvs: usize, normals: usize) -> Result<Corner, LoadError> {
+    let parts: Vec<&str> = text.split('/').collect();
+
+    if parts.len() > 3 || parts[0].is_empty() {
+        return Err(LoadError::parse(line, format!("malformed face vertex '{}'", text)));
+    }
+
+    let optional = |part: Option<&&str>, count: usize, what: &str| -> Result<Option<usize>, LoadError> {
+        match part {
+            Some(p) if !p.is_empty() => resolveIndex(line, p, count, what).map(Some),
+            _ => Ok(None)
+        }
+    };
+
+    Ok(Corner {
+        position: resolveIndex(line, parts[0], positions, "position")?,
+        uv: optional(parts.get(1), uvs, "texture coordinate")?,
+        normal: optional(parts.get(2), normals, "normal")?
+    })
+}
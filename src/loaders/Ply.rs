@@ -0,0 +1,448 @@
+use std::fs;
+use std::path::Path;
+
+use crate::vectors::{Vector2::*, Vector3::*};
+use crate::rasterizer::Color::*;
+use crate::mesh::Mesh::*;
+use crate::loaders::LoadError::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Scalar {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Property {
+    Scalar { name: String, kind: Scalar },
+    List { name: String, count: Scalar, item: Scalar }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>
+}
+
+// Reads property values from the body, either as whitespace separated text
+// with one element per line or as packed little-endian binary.
+struct Body<'a> {
+    format: Format,
+    bytes: &'a [u8],
+    offset: usize,
+    // The line last read, for error messages.
+    line: usize,
+    tokens: Vec<&'a str>,
+    next: usize
+}
+
+pub struct PlyLoader;
+
+impl PlyLoader {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Mesh, LoadError> {
+        PlyLoader::parse(&fs::read(path)?)
+    }
+
+    // Parses ASCII or binary little-endian PLY. Vertices keep their normals,
+    // texture coordinates and colors when the file has them, faces with more
+    // than three corners are split into a fan and other elements are skipped.
+    pub fn parse(bytes: &[u8]) -> Result<Mesh, LoadError> {
+        let (format, elements, bodyStart, headerLines) = parseHeader(bytes)?;
+
+        let mut body = Body {
+            format,
+            bytes,
+            offset: bodyStart,
+            line: headerLines,
+            tokens: Vec::new(),
+            next: 0
+        };
+
+        let mut mesh = Mesh::default();
+        let mut vertexCount: Option<usize> = None;
+        let mut faces: Vec<Vec<u32>> = Vec::new();
+
+        for element in elements.iter() {
+            match element.name.as_str() {
+                "vertex" => {
+                    for _ in 0..element.count {
+                        body.beginElement(&element.name)?;
+                        mesh.addVertex(readVertex(&mut body, element)?);
+                    }
+
+                    vertexCount = Some(element.count);
+                },
+                "face" => {
+                    for _ in 0..element.count {
+                        body.beginElement(&element.name)?;
+                        faces.push(readFace(&mut body, element)?);
+                    }
+                },
+                _ => {
+                    for _ in 0..element.count {
+                        body.beginElement(&element.name)?;
+
+                        for property in element.properties.iter() {
+                            body.skip(property)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        body.finish()?;
+
+        let vertexCount = vertexCount.unwrap_or(0);
+
+        for (number, face) in faces.iter().enumerate() {
+            if face.len() < 3 {
+                return Err(LoadError::Format(format!("face {} needs at least 3 vertices, found {}", number, face.len())));
+            }
+
+            if let Some(index) = face.iter().find(|i| **i as usize >= vertexCount) {
+                return Err(LoadError::Format(format!("face {} uses vertex {} but only {} are defined", number, index, vertexCount)));
+            }
+
+            for k in 1..face.len() - 1 {
+                mesh.addTriangle(face[0], face[k], face[k + 1]);
+            }
+        }
+
+        Ok(mesh)
+    }
+}
+
+fn readVertex(body: &mut Body, element: &Element) -> Result<Vertex, LoadError> {
+    let mut vertex = Vertex::default();
+    let mut position = [0.0; 3];
+    let mut normal = [0.0; 3];
+    let mut uv = [0.0; 2];
+    let mut color: [Option<f64>; 3] = [None; 3];
+
+    for property in element.properties.iter() {
+        let (name, kind) = match property {
+            Property::Scalar { name, kind } => (name.as_str(), *kind),
+            Property::List { .. } => {
+                body.skip(property)?;
+                continue;
+            }
+        };
+
+        let value = body.read(kind, name)?;
+
+        match name {
+            "x" => position[0] = value,
+            "y" => position[1] = value,
+            "z" => position[2] = value,
+            "nx" => normal[0] = value,
+            "ny" => normal[1] = value,
+            "nz" => normal[2] = value,
+            "u" | "s" | "texture_u" | "texture_s" => uv[0] = value,
+            "v" | "t" | "texture_v" | "texture_t" => uv[1] = value,
+            // Integer channels run 0-255, floating point ones 0-1.
+            "red" | "green" | "blue" | "diffuse_red" | "diffuse_green" | "diffuse_blue" => {
+                let channel = match name.trim_start_matches("diffuse_") {
+                    "red" => 0,
+                    "green" => 1,
+                    _ => 2
+                };
+
+                color[channel] = Some(if kind.isFloat() { value } else { value / 255.0 });
+            },
+            _ => {}
+        }
+    }
+
+    vertex.position = Vector3::new(position[0], position[1], position[2]);
+    vertex.normal = Vector3::new(normal[0], normal[1], normal[2]);
+    vertex.uv = Vector2::new(uv[0], uv[1]);
+
+    if color.iter().any(|c| c.is_some()) {
+        vertex.color = Color::fromFloats(color[0].unwrap_or(0.0), color[1].unwrap_or(0.0), color[2].unwrap_or(0.0));
+    }
+
+    Ok(vertex)
+}
+
+fn readFace(body: &mut Body, element: &Element) -> Result<Vec<u32>, LoadError> {
+    let mut corners: Vec<u32> = Vec::new();
+
+    for property in element.properties.iter() {
+        match property {
+            Property::List { name, count, item } if name == "vertex_indices" || name == "vertex_index" => {
+                if item.isFloat() {
+                    return Err(LoadError::Format(format!("{} must hold integers, not floating point values", name)));
+                }
+
+                let length = body.read(*count, name)?;
+
+                if length < 0.0 {
+                    return Err(body.error(format!("negative length {} in {}", length, name)));
+                }
+
+                for _ in 0..length as usize {
+                    let index = body.read(*item, name)?;
+
+                    if index < 0.0 || index > u32::MAX as f64 {
+                        return Err(body.error(format!("vertex index {} out of range", index)));
+                    }
+
+                    if index.fract() != 0.0 {
+                        return Err(LoadError::Format(format!("vertex index {} is not a whole number", index)));
+                    }
+
+                    corners.push(index as u32);
+                }
+            },
+            _ => body.skip(property)?
+        }
+    }
+
+    Ok(corners)
+}
+
+// Returns the format, the declared elements, the byte offset where the body
+// starts and the line number of end_header.
+fn parseHeader(bytes: &[u8]) -> Result<(Format, Vec<Element>, usize, usize), LoadError> {
+    let mut format: Option<Format> = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut offset = 0;
+    let mut number = 0;
+
+    loop {
+        let end = match bytes[offset..].iter().position(|b| *b == b'\n') {
+            Some(end) => offset + end,
+            None => return Err(LoadError::Format("PLY header has no end_header".to_string()))
+        };
+
+        number += 1;
+
+        let line = std::str::from_utf8(&bytes[offset..end])
+            .map_err(|_| LoadError::parse(number, "PLY header is not valid UTF-8"))?;
+
+        offset = end + 1;
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        if number == 1 {
+            if tokens != ["ply"] {
+                return Err(LoadError::Format("not a PLY file: missing 'ply' magic".to_string()));
+            }
+
+            continue;
+        }
+
+        match tokens.first().copied() {
+            Some("format") => {
+                format = Some(match tokens.get(1).copied() {
+                    Some("ascii") => Format::Ascii,
+                    Some("binary_little_endian") => Format::BinaryLittleEndian,
+                    Some(other) => return Err(LoadError::parse(number, format!("unsupported PLY format '{}'", other))),
+                    None => return Err(LoadError::parse(number, "format needs a name"))
+                });
+            },
+            Some("element") => {
+                if tokens.len() != 3 {
+                    return Err(LoadError::parse(number, "element needs a name and a count"));
+                }
+
+                let count = tokens[2].parse::<usize>()
+                    .map_err(|_| LoadError::parse(number, format!("invalid element count '{}'", tokens[2])))?;
+
+                elements.push(Element {
+                    name: tokens[1].to_string(),
+                    count,
+                    properties: Vec::new()
+                });
+            },
+            Some("property") => {
+                let element = elements.last_mut()
+                    .ok_or_else(|| LoadError::parse(number, "property before any element"))?;
+
+                let property = match tokens.get(1).copied() {
+                    Some("list") if tokens.len() == 5 => Property::List {
+                        name: tokens[4].to_string(),
+                        count: Scalar::parse(number, tokens[2])?,
+                        item: Scalar::parse(number, tokens[3])?
+                    },
+                    Some("list") => return Err(LoadError::parse(number, "list property needs a count type, an item type and a name")),
+                    Some(kind) if tokens.len() == 3 => Property::Scalar {
+                        name: tokens[2].to_string(),
+                        kind: Scalar::parse(number, kind)?
+                    },
+                    _ => return Err(LoadError::parse(number, "property needs a type and a name"))
+                };
+
+                element.properties.push(property);
+            },
+            Some("end_header") => break,
+            // comment, obj_info and blank lines.
+            _ => {}
+        }
+    }
+
+    let format = format.ok_or_else(|| LoadError::Format("PLY header has no format line".to_string()))?;
+
+    Ok((format, elements, offset, number))
+}
+
+impl Scalar {
+    fn parse(line: usize, name: &str) -> Result<Scalar, LoadError> {
+        Ok(match name {
+            "char" | "int8" => Scalar::Int8,
+            "uchar" | "uint8" => Scalar::UInt8,
+            "short" | "int16" => Scalar::Int16,
+            "ushort" | "uint16" => Scalar::UInt16,
+            "int" | "int32" => Scalar::Int32,
+            "uint" | "uint32" => Scalar::UInt32,
+            "float" | "float32" => Scalar::Float32,
+            "double" | "float64" => Scalar::Float64,
+            _ => return Err(LoadError::parse(line, format!("unknown property type '{}'", name)))
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::Int8 | Scalar::UInt8 => 1,
+            Scalar::Int16 | Scalar::UInt16 => 2,
+            Scalar::Int32 | Scalar::UInt32 | Scalar::Float32 => 4,
+            Scalar::Float64 => 8
+        }
+    }
+
+    fn isFloat(self) -> bool {
+        self == Scalar::Float32 || self == Scalar::Float64
+    }
+}
+
+impl<'a> Body<'a> {
+    // In ASCII files each element sits on its own line.
+    fn beginElement(&mut self, name: &str) -> Result<(), LoadError> {
+        if self.format == Format::BinaryLittleEndian {
+            return Ok(());
+        }
+
+        if self.next < self.tokens.len() {
+            return Err(LoadError::parse(self.line, format!("too many values for {}", name)));
+        }
+
+        loop {
+            if self.offset >= self.bytes.len() {
+                return Err(LoadError::parse(self.line, format!("file ends before all {} elements", name)));
+            }
+
+            let end = self.bytes[self.offset..].iter()
+                .position(|b| *b == b'\n')
+                .map(|end| self.offset + end)
+                .unwrap_or(self.bytes.len());
+
+            self.line += 1;
+
+            let line = std::str::from_utf8(&self.bytes[self.offset..end])
+                .map_err(|_| LoadError::parse(self.line, "body is not valid UTF-8"))?;
+
+            self.offset = end + 1;
+            self.tokens = line.split_whitespace().collect();
+            self.next = 0;
+
+            if !self.tokens.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read(&mut self, kind: Scalar, name: &str) -> Result<f64, LoadError> {
+        match self.format {
+            Format::Ascii => {
+                let token = self.tokens.get(self.next)
+                    .ok_or_else(|| LoadError::parse(self.line, format!("missing value for '{}'", name)))?;
+
+                self.next += 1;
+
+                token.parse::<f64>()
+                    .ok()
+                    .filter(|v| v.is_finite())
+                    .ok_or_else(|| LoadError::parse(self.line, format!("invalid number '{}' for '{}'", token, name)))
+            },
+            Format::BinaryLittleEndian => {
+                let size = kind.size();
+
+                if self.offset + size > self.bytes.len() {
+                    return Err(LoadError::Format(format!("unexpected end of data reading '{}' at byte {}", name, self.offset)));
+                }
+
+                let b = &self.bytes[self.offset..self.offset + size];
+                self.offset += size;
+
+                let value = match kind {
+                    Scalar::Int8 => b[0] as i8 as f64,
+                    Scalar::UInt8 => b[0] as f64,
+                    Scalar::Int16 => i16::from_le_bytes([b[0], b[1]]) as f64,
+                    Scalar::UInt16 => u16::from_le_bytes([b[0], b[1]]) as f64,
+                    Scalar::Int32 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                    Scalar::UInt32 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                    Scalar::Float32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                    Scalar::Float64 => f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+                };
+
+                if !value.is_finite() {
+                    return Err(LoadError::Format(format!("invalid number {} for '{}' at byte {}", value, name, self.offset - size)));
+                }
+
+                Ok(value)
+            }
+        }
+    }
+
+    // In ASCII files nothing but blank lines may follow the last element.
+    fn finish(&mut self) -> Result<(), LoadError> {
+        if self.format == Format::BinaryLittleEndian {
+            return Ok(());
+        }
+
+        let rest = self.bytes.get(self.offset..).unwrap_or(&[]);
+
+        if self.next < self.tokens.len() || rest.iter().any(|b| !b.is_ascii_whitespace()) {
+            return Err(LoadError::Format(format!("unexpected data after the last element on line {}", self.line)));
+        }
+
+        Ok(())
+    }
+
+    fn skip(&mut self, property: &Property) -> Result<(), LoadError> {
+        match property {
+            Property::Scalar { name, kind } => {
+                self.read(*kind, name)?;
+            },
+            Property::List { name, count, item } => {
+                let length = self.read(*count, name)?;
+
+                for _ in 0..length.max(0.0) as usize {
+                    self.read(*item, name)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn error(&self, message: String) -> LoadError {
+        match self.format {
+            Format::Ascii => LoadError::parse(self.line, message),
+            Format::BinaryLittleEndian => LoadError::Format(message)
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::fs;
+use std::path::Path;
+
+use crate::vectors::{VectorProperties::*, Vector2::*, Vector3::*};
+use crate::rasterizer::Color::*;
+use crate::mesh::Mesh::*;
+use crate::loaders::LoadError::*;
+
+const HEADER_SIZE: usize = 80;
+const FACET_SIZE: usize = 50;
+
+pub struct StlLoader;
+
+impl StlLoader {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Mesh, LoadError> {
+        StlLoader::parse(&fs::read(path)?)
+    }
+
+    // Parses ASCII or binary STL. Every facet gets its own three vertices
+    // carrying the facet normal, so the mesh shades flat. Facets stored
+    // without a normal get one from their winding.
+    pub fn parse(bytes: &[u8]) -> Result<Mesh, LoadError> {
+        // Binary files may also start with "solid", so trust the size first.
+        if bytes.len() >= HEADER_SIZE + 4 {
+            let count = readU32(bytes, HEADER_SIZE) as usize;
+
+            if bytes.len() == HEADER_SIZE + 4 + count * FACET_SIZE {
+                return StlLoader::parseBinary(bytes, count);
+            }
+        }
+
+        let start = bytes.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(bytes.len());
+
+        if bytes[start..].starts_with(b"solid") {
+            let text = std::str::from_utf8(bytes)
+                .map_err(|_| LoadError::Format("ASCII STL is not valid UTF-8".to_string()))?;
+
+            return StlLoader::parseAscii(text);
+        }
+
+        Err(LoadError::Format(format!("not an STL file: {} bytes is not a whole number of binary facets and there is no 'solid' header", bytes.len())))
+    }
+
+    fn parseBinary(bytes: &[u8], count: usize) -> Result<Mesh, LoadError> {
+        let mut mesh = Mesh::default();
+
+        for facet in 0..count {
+            let offset = HEADER_SIZE + 4 + facet * FACET_SIZE;
+            let vector = |k: usize| -> Result<Vector3, LoadError> {
+                let at = offset + k * 12;
+                Ok(Vector3::new(readF32(bytes, at)?, readF32(bytes, at + 4)?, readF32(bytes, at + 8)?))
+            };
+
+            addFacet(&mut mesh, vector(0)?, [vector(1)?, vector(2)?, vector(3)?]);
+        }
+
+        Ok(mesh)
+    }
+
+    fn parseAscii(text: &str) -> Result<Mesh, LoadError> {
+        let mut mesh = Mesh::default();
+        let mut normal: Option<Vector3> = None;
+        let mut corners: Vec<Vector3> = Vec::with_capacity(3);
+
+        for (number, line) in text.lines().enumerate() {
+            let number = number + 1;
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+
+            match tokens.first().copied() {
+                Some("facet") => {
+                    if normal.is_some() {
+                        return Err(LoadError::parse(number, "facet inside another facet"));
+                    }
+
+                    if tokens.get(1) != Some(&"normal") {
+                        return Err(LoadError::parse(number, "expected 'facet normal'"));
+                    }
+
+                    normal = Some(parseVector(number, &tokens[2..], "facet normal")?);
+                    corners.clear();
+                },
+                Some("vertex") => {
+                    if normal.is_none() {
+                        return Err(LoadError::parse(number, "vertex outside a facet"));
+                    }
+
+                    if corners.len() == 3 {
+                        return Err(LoadError::parse(number, "facet has more than 3 vertices"));
+                    }
+
+                    corners.push(parseVector(number, &tokens[1..], "vertex")?);
+                },
+                Some("endfacet") => {
+                    let n = normal.take()
+                        .ok_or_else(|| LoadError::parse(number, "endfacet without facet"))?;
+
+                    if corners.len() != 3 {
+                        return Err(LoadError::parse(number, format!("facet has {} vertices, expected 3", corners.len())));
+                    }
+
+                    addFacet(&mut mesh, n, [corners[0], corners[1], corners[2]]);
+                },
+                Some("endsolid") if normal.is_some() => {
+                    return Err(LoadError::parse(number, "endsolid inside a facet"));
+                },
+                // solid, outer loop and endloop carry nothing we need.
+                _ => {}
+            }
+        }
+
+        if normal.is_some() {
+            return Err(LoadError::parse(text.lines().count(), "file ends inside a facet"));
+        }
+
+        Ok(mesh)
+    }
+}
+
+fn addFacet(mesh: &mut Mesh, normal: Vector3, corners: [Vector3; 3]) {
+    let winding = Vector3::cross(&(corners[1] - corners[0]), &(corners[2] - corners[0]));
+
+    // Degenerate facets without a stored normal keep a zero one.
+    let normal = if Vector3::length(&normal) > 0.0 {
+        Vector3::getNormalized(&normal)
+    } else if Vector3::length(&winding) > 0.0 {
+        Vector3::getNormalized(&winding)
+    } else {
+        Vector3::new(0.0, 0.0, 0.0)
+    };
+
+    let first = mesh.getVertices().len() as u32;
+
+    for corner in corners.iter() {
+        mesh.addVertex(Vertex::new(*corner, normal, Vector2::new(0.0, 0.0), Color::WHITE));
+    }
+
+    mesh.addTriangle(first, first + 1, first + 2);
+}
+
+fn parseVector(line: usize, args: &[&str], what: &str) -> Result<Vector3, LoadError> {
+    if args.len() != 3 {
+        return Err(LoadError::parse(line, format!("{} needs 3 numbers, found {}", what, args.len())));
+    }
+
+    let mut values = [0.0; 3];
+
+    for (value, arg) in values.iter_mut().zip(args.iter()) {
+        *value = arg.parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| LoadError::parse(line, format!("invalid number '{}' in {}", arg, what)))?;
+    }
+
+    Ok(Vector3::new(values[0], values[1], values[2]))
+}
+
+fn readU32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+fn readF32(bytes: &[u8], at: usize) -> Result<f64, LoadError> {
+    let value = f32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+
+    if !value.is_finite() {
+        return Err(LoadError::Format(format!("invalid number {} at byte {}", value, at)));
+    }
+
+    Ok(value as f64)
+}
@@ -0,0 +1,7 @@
+pub mod LoadError;
+pub mod Obj;
+pub mod Stl;
+pub mod Ply;
+pub mod Gltf;
+
+mod Json;
@@ -1,22 +1,99 @@
 #![allow(non_snake_case)]
+extern crate ncurses_rendering_engine;
 extern crate pancurses;
 
-use pancurses::{initscr, endwin, raw, noecho};
+use pancurses::{initscr, endwin, raw, noecho, Input};
+use std::{env, process};
+use std::path::Path;
 
-mod vectors;
-mod matrices;
-mod quaternions;
-mod rasterizer;
-mod math_utils;
+use ncurses_rendering_engine::rasterizer::{Rasterizer::*, RenderTarget::*, Color::*, FrameBuffer::*};
+use ncurses_rendering_engine::pipeline::Pipeline::*;
+use ncurses_rendering_engine::camera::{Camera::*, Controllers::*};
+use ncurses_rendering_engine::matrices::Matrix44::*;
+use ncurses_rendering_engine::mesh::{Mesh::*, Primitives::*};
+use ncurses_rendering_engine::loaders::{LoadError::*, Obj::*, Stl::*, Ply::*, Gltf::*};
+use ncurses_rendering_engine::lighting::{Light::*, Lighting::*};
+use ncurses_rendering_engine::vectors::{VectorProperties::*, Vector3::*};
+use ncurses_rendering_engine::math_utils::GeneralMath;
 
-mod defs;
+// Centers the mesh on the origin and scales it to fit a unit sphere, so any
+// model fits the orbit camera's view.
+fn fitToUnitSphere(mesh: &mut Mesh) {
+    let vertices = mesh.getVerticesMut();
 
-use self::rasterizer::*;
-use self::matrices::Matrix44::*;
-use self::vectors::{Vector2::*, Vector3::*, Vector4::*};
-use self::math_utils::GeneralMath;
+    if vertices.is_empty() {
+        return;
+    }
+
+    let mut min = vertices[0].position;
+    let mut max = vertices[0].position;
+
+    for v in vertices.iter() {
+        min = Vector3::new(min.x.min(v.position.x), min.y.min(v.position.y), min.z.min(v.position.z));
+        max = Vector3::new(max.x.max(v.position.x), max.y.max(v.position.y), max.z.max(v.position.z));
+    }
+
+    let center = Vector3::new((min.x + max.x) * 0.5, (min.y + max.y) * 0.5, (min.z + max.z) * 0.5);
+    let radius = vertices.iter().map(|v| Vector3::dist(&v.position, &center)).fold(0.0, f64::max);
+    let scale = if radius > 0.0 { 1.0 / radius } else { 1.0 };
+
+    for v in vertices.iter_mut() {
+        let mut p = v.position - center;
+        p.scale(scale);
+        v.position = p;
+    }
+}
+
+// Picks a loader from the file extension, treating anything unknown as OBJ.
+fn loadMesh(path: &str) -> Result<Mesh, LoadError> {
+    let extension = Path::new(path).extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("stl") => StlLoader::load(path),
+        Some("ply") => PlyLoader::load(path),
+        Some("gltf") | Some("glb") => GltfLoader::load(path).map(|model| model.toMesh()),
+        _ => ObjLoader::load(path).map(|model| model.mesh)
+    }
+}
 
 fn main() {
+    // `cargo run -- halfblock` or `cargo run -- braille` for sub-cell output,
+    // plus the name of a built-in shape or the path of an .obj, .stl, .ply,
+    // .gltf or .glb file to view it instead of the triangle.
+    let mut mode = PresentMode::Cells;
+    let mut mesh = Mesh::new(vec![
+        Vertex::fromPosition(Vector3::new(-1.0, -1.0, 0.0)),
+        Vertex::fromPosition(Vector3::new(1.0, -1.0, 0.0)),
+        Vertex::fromPosition(Vector3::new(0.0, 1.0, 0.0))
+    ], vec![0, 1, 2]);
+
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "halfblock" => mode = PresentMode::HalfBlock,
+            "braille" => mode = PresentMode::Braille,
+            "cube" => mesh = Primitives::cube(1.4, 1),
+            "sphere" => mesh = Primitives::uvSphere(1.0, 24, 12),
+            "icosphere" => mesh = Primitives::icosphere(1.0, 2),
+            "cylinder" => mesh = Primitives::cylinder(0.7, 1.4, 24, 1),
+            "cone" => mesh = Primitives::cone(0.8, 1.4, 24, 1),
+            "torus" => mesh = Primitives::torus(0.7, 0.3, 32, 12),
+            "plane" => mesh = Primitives::plane(2.0, 2.0, 8, 8),
+            "capsule" => mesh = Primitives::capsule(0.5, 0.8, 24, 6),
+            path => match loadMesh(path) {
+                Ok(loaded) => {
+                    mesh = loaded;
+                    fitToUnitSphere(&mut mesh);
+                },
+                Err(e) => {
+                    eprintln!("{}: {}", path, e);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+
     let window = initscr();
     raw();
     noecho();
@@ -24,72 +101,74 @@ fn main() {
     let LINES = window.get_max_y();
     let COLS = window.get_max_x();
 
-    let rast = Rasterizer::new(LINES * 10, COLS * 10, &window);
+    let mut target = CursesTarget::new(&window);
+
+    let mut rast = Rasterizer::withFrameBuffer(FrameBuffer::forTerminal(COLS, LINES, mode));
+    rast.setColor(Color::new(80, 200, 255));
+    let mut pipeline = Pipeline::new(Viewport::fromFrameBuffer(rast.getFrameBuffer()));
+
+    let mut camera = Camera::new(Vector3::new(0.0, 0.0, 3.0), Projection::Perspective {
+        fovY: GeneralMath::toRadians(60.0),
+        near: 0.1,
+        far: 100.0
+    }, GeneralMath::terminalAspect(COLS, LINES));
+
+    let mut orbit = OrbitController::new(Vector3::new(0.0, 0.0, 0.0), 3.0);
+    orbit.apply(&mut camera);
+
+    // A key light from the upper left over a little ambient. Flat shading
+    // suits any mesh since it does not need vertex normals.
+    let mut lighting = Lighting::new();
+    lighting.setShadingModel(ShadingModel::Flat);
+    lighting.addLight(Light::Ambient { color: Color::WHITE, intensity: 0.15 });
+    lighting.addLight(Light::Directional { direction: Vector3::new(1.0, -1.0, -1.0), color: Color::WHITE, intensity: 0.85 });
+
+    window.keypad(true);
 
     let mut angle: f64 = 0.0;
 
-    while angle < 1.0 {
+    loop {
+        rast.getFrameBufferMut().clear();
+
+        pipeline.setProjection(&camera.getProjectionMatrix());
+        pipeline.setView(&camera.getViewMatrix());
+
         let mut transformation: Matrix44 = Default::default();
-        transformation.scale(&Vector3{
-            x: 4.0,
+        transformation.rotate(&Vector3{
+            x: 0.0,
             y: 1.0,
-            z: 1.0 
-        });
-
-        transformation.translate(&Vector3::new(0.0, 20.0, 0.0));
-    
-        // transformation.rotate(&Vector3{
-        //     x: 0.5,
-        //     y: 0.5,
-        //     z: 0.
-        // }, GeneralMath::toRadians(angle));
-    
-        let mut v1: Vector4 = Vector4 {
-            x: 10.0,
-            y: 10.0,
-            z: 1.0,
-            w: 1.0
-        };
-    
-        let mut v2: Vector4 = Vector4 {
-            x: 20.0,
-            y: 10.0,
-            z: 1.0,
-            w: 1.0
-        };
-    
-        let mut v3: Vector4 = Vector4 {
-            x: 15.0,
-            y: 20.0,
-            z: 1.0,
-            w: 1.0
-        };
-    
-        v1 = transformation * v1;
-        v2 = transformation * v2;
-        v3 = transformation * v3;
-    
-        rast.rasterizeTriangle(&Vector2{
-            x: v1.x,
-            y: v1.y
-        }, &Vector2{
-            x: v2.x,
-            y: v2.y
-        }, &Vector2 {
-            x: v3.x,
-            y: v3.y 
-        });
-
-        window.getch();
-        window.clear();
+            z: 0.0
+        }, GeneralMath::toRadians(angle));
+        pipeline.setModel(&transformation);
+
+        rast.drawLitMesh(&mesh, &pipeline, &lighting);
+
+        rast.getFrameBuffer().present(&mut target);
+
+        match window.getch() {
+            Some(Input::Character('q')) => break,
+            Some(Input::Character('m')) => {
+                rast.setPolygonMode(match rast.getPolygonMode() {
+                    PolygonMode::Fill => PolygonMode::Line,
+                    PolygonMode::Line => PolygonMode::Point,
+                    PolygonMode::Point => PolygonMode::Fill
+                });
+            },
+            Some(Input::Character('l')) => {
+                lighting.setShadingModel(match lighting.getShadingModel() {
+                    ShadingModel::Flat => ShadingModel::Gouraud,
+                    ShadingModel::Gouraud => ShadingModel::Phong,
+                    ShadingModel::Phong => ShadingModel::Flat
+                });
+            },
+            Some(input) => {
+                orbit.handleInput(&input, &mut camera);
+            },
+            None => {}
+        }
 
         angle += 5.0;
     }
 
-    // window.printw("Hello World!");
-    // window.refresh();
-    window.getch();
-    window.clear();
-
     endwin();
 }
@@ -14,4 +14,9 @@ impl GeneralMath{
     pub fn toDegrees(radians: f64) -> f64 {
         (180.0/PI) * radians
     }
+
+    // Physical width/height ratio of a grid of terminal cells.
+    pub fn terminalAspect(cols: i32, rows: i32) -> f64 {
+        cols as f64 / (rows as f64 * CELL_ASPECT)
+    }
 }
\ No newline at end of file
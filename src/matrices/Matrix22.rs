@@ -84,9 +84,7 @@ impl MatrixProperties for Matrix22 {
     } 
 
     fn transpose(&mut self) {
-        let temp: f64 = self.m01;
-        self.m01 = self.m10;
-        self.m10 = temp;
+        std::mem::swap(&mut self.m01, &mut self.m10);
     }
 
     fn invert(&mut self) {
@@ -135,7 +133,7 @@ impl ops::Add<Matrix22> for Matrix22 {
 
 impl ops::AddAssign<Matrix22> for Matrix22 {
     fn add_assign(&mut self, rhs: Matrix22) {
-        *self = Matrix22::add(&self, &rhs);
+        *self = Matrix22::add(self, &rhs);
     }
 }
 
@@ -149,7 +147,7 @@ impl ops::Sub<Matrix22> for Matrix22 {
 
 impl ops::SubAssign<Matrix22> for Matrix22 {
     fn sub_assign(&mut self, rhs: Matrix22) {
-        *self = Matrix22::sub(&self, &rhs)
+        *self = Matrix22::sub(self, &rhs)
     }
 }
 
@@ -171,6 +169,6 @@ impl ops::Mul<Vector2> for Matrix22 {
 
 impl ops::MulAssign<Matrix22> for Matrix22 {
     fn mul_assign(&mut self, rhs: Matrix22) {
-        *self = Matrix22::mul(&self, &rhs)
+        *self = Matrix22::mul(self, &rhs)
     }
 }
\ No newline at end of file
@@ -196,9 +196,9 @@ impl MatrixProperties for Matrix33 {
     }
 
     fn det(&self) -> f64 {
-        return self.m00 * (self.m11 * self.m22 - self.m12 * self.m21)
+        self.m00 * (self.m11 * self.m22 - self.m12 * self.m21)
                 + self.m01 * (self.m12 * self.m20 - self.m10 * self.m22)
-                + self.m02 * (self.m10 * self.m21 - self.m11 * self.m20);
+                + self.m02 * (self.m10 * self.m21 - self.m11 * self.m20)
     }
 
     fn print(&self) {
@@ -219,7 +219,7 @@ impl ops::Add<Matrix33> for Matrix33 {
 
 impl ops::AddAssign<Matrix33> for Matrix33 {
     fn add_assign(&mut self, rhs: Matrix33) {
-        *self = Matrix33::add(&self, &rhs);
+        *self = Matrix33::add(self, &rhs);
     }
 }
 
@@ -233,7 +233,7 @@ impl ops::Sub<Matrix33> for Matrix33 {
 
 impl ops::SubAssign<Matrix33> for Matrix33 {
     fn sub_assign(&mut self, rhs: Matrix33) {
-        *self = Matrix33::sub(&self, &rhs)
+        *self = Matrix33::sub(self, &rhs)
     }
 }
 
@@ -255,6 +255,6 @@ impl ops::Mul<Vector3> for Matrix33 {
 
 impl ops::MulAssign<Matrix33> for Matrix33 {
     fn mul_assign(&mut self, rhs: Matrix33) {
-        *self = Matrix33::mul(&self, &rhs)
+        *self = Matrix33::mul(self, &rhs)
     }
 }
\ No newline at end of file
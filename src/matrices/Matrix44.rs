@@ -1,5 +1,7 @@
 use crate::matrices::MatrixProperties::*;
+use crate::vectors::VectorProperties::*;
 use crate::vectors::{Vector4::*, Vector3::*};
+use crate::math_utils::GeneralMath;
 use std::ops;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -106,8 +108,79 @@ impl Matrix44 {
         self.m32 += self.m02 * v.x + self.m12 * v.y + self.m22 * v.z;
         self.m33 += self.m03 * v.x + self.m13 * v.y + self.m23 * v.z;
     }
+
+    // Right-handed perspective projection looking down -z, mapping the view
+    // volume to OpenGL-style clip space. `fovY` is in radians.
+    pub fn perspective(fovY: f64, aspect: f64, near: f64, far: f64) -> Matrix44 {
+        let f: f64 = 1.0 / (fovY * 0.5).tan();
+
+        Matrix44 {
+            m00: f / aspect, m01: 0., m02: 0., m03: 0.,
+            m10: 0., m11: f, m12: 0., m13: 0.,
+            m20: 0., m21: 0., m22: (far + near) / (near - far), m23: -1.,
+            m30: 0., m31: 0., m32: (2.0 * far * near) / (near - far), m33: 0.,
+        }
+    }
+
+    // Perspective projection for an off-center view volume, as glFrustum.
+    pub fn frustum(left: f64, right: f64, bottom: f64, top: f64, near: f64, far: f64) -> Matrix44 {
+        Matrix44 {
+            m00: (2.0 * near) / (right - left), m01: 0., m02: 0., m03: 0.,
+            m10: 0., m11: (2.0 * near) / (top - bottom), m12: 0., m13: 0.,
+            m20: (right + left) / (right - left),
+            m21: (top + bottom) / (top - bottom),
+            m22: -(far + near) / (far - near),
+            m23: -1.,
+            m30: 0., m31: 0., m32: -(2.0 * far * near) / (far - near), m33: 0.,
+        }
+    }
+
+    pub fn orthographic(left: f64, right: f64, bottom: f64, top: f64, near: f64, far: f64) -> Matrix44 {
+        Matrix44 {
+            m00: 2.0 / (right - left), m01: 0., m02: 0., m03: 0.,
+            m10: 0., m11: 2.0 / (top - bottom), m12: 0., m13: 0.,
+            m20: 0., m21: 0., m22: -2.0 / (far - near), m23: 0.,
+            m30: -(right + left) / (right - left),
+            m31: -(top + bottom) / (top - bottom),
+            m32: -(far + near) / (far - near),
+            m33: 1.,
+        }
+    }
+
+    // View matrix for an eye at `eye` looking towards `target`, as gluLookAt.
+    // Looking straight along `up` leaves the roll undefined, so another up
+    // axis is used then: -z, or +y when looking along z.
+    pub fn lookAt(eye: &Vector3, target: &Vector3, up: &Vector3) -> Matrix44 {
+        let f: Vector3 = Vector3::getNormalized(&(*target - *eye));
+        let mut side: Vector3 = Vector3::cross(&f, up);
+
+        if side.length() < 1e-12 {
+            let fallback = if f.z.abs() < 0.9 { Vector3::new(0.0, 0.0, -1.0) } else { Vector3::new(0.0, 1.0, 0.0) };
+            side = Vector3::cross(&f, &fallback);
+        }
+
+        let s: Vector3 = Vector3::getNormalized(&side);
+        let u: Vector3 = Vector3::cross(&s, &f);
+
+        Matrix44 {
+            m00: s.x, m01: u.x, m02: -f.x, m03: 0.,
+            m10: s.y, m11: u.y, m12: -f.y, m13: 0.,
+            m20: s.z, m21: u.z, m22: -f.z, m23: 0.,
+            m30: -Vector3::dot(&s, eye),
+            m31: -Vector3::dot(&u, eye),
+            m32: Vector3::dot(&f, eye),
+            m33: 1.,
+        }
+    }
+
+    // Perspective projection sized for a terminal of `cols` x `rows` cells,
+    // correcting for cells being taller than they are wide.
+    pub fn terminalPerspective(fovY: f64, cols: i32, rows: i32, near: f64, far: f64) -> Matrix44 {
+        Matrix44::perspective(fovY, GeneralMath::terminalAspect(cols, rows), near, far)
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn DET33(t00: f64, t01: f64, t02: f64, t10: f64, t11: f64, t12: f64, t20: f64, t21: f64, t22: f64) -> f64 {
     (t00 * (t11 * t22 - t12 * t21)) + (t01 * (t12 * t20 - t10 * t22)) + (t02 * (t10 * t21 - t11 * t20))
 }
@@ -381,7 +454,7 @@ impl MatrixProperties for Matrix44 {
             - self.m12 * self.m21 * self.m30
             - self.m10 * self.m22 * self.m31
             - self.m11 * self.m20 * self.m32);
-        return f;
+        f
     }
 
     fn print(&self) {
@@ -403,7 +476,7 @@ impl ops::Add<Matrix44> for Matrix44 {
 
 impl ops::AddAssign<Matrix44> for Matrix44 {
     fn add_assign(&mut self, rhs: Matrix44) {
-        *self = Matrix44::add(&self, &rhs);
+        *self = Matrix44::add(self, &rhs);
     }
 }
 
@@ -417,7 +490,7 @@ impl ops::Sub<Matrix44> for Matrix44 {
 
 impl ops::SubAssign<Matrix44> for Matrix44 {
     fn sub_assign(&mut self, rhs: Matrix44) {
-        *self = Matrix44::sub(&self, &rhs)
+        *self = Matrix44::sub(self, &rhs)
     }
 }
 
@@ -439,6 +512,6 @@ impl ops::Mul<Vector4> for Matrix44 {
 
 impl ops::MulAssign<Matrix44> for Matrix44 {
     fn mul_assign(&mut self, rhs: Matrix44) {
-        *self = Matrix44::mul(&self, &rhs)
+        *self = Matrix44::mul(self, &rhs)
     }
 }
\ No newline at end of file
@@ -0,0 +1,20 @@
+use crate::rasterizer::Color::*;
+
+// Surface properties shared by the model loaders. Only what the rasterizer
+// can show is kept: a diffuse color and the path of a diffuse texture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    pub diffuse: Color,
+    pub texture: Option<String>
+}
+
+impl Material {
+    pub fn new(name: &str) -> Material {
+        Material {
+            name: name.to_string(),
+            diffuse: Color::WHITE,
+            texture: None
+        }
+    }
+}
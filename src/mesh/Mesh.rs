@@ -0,0 +1,100 @@
+use crate::vectors::{Vector2::*, Vector3::*};
+use crate::rasterizer::Color::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Vertex {
+    pub position: Vector3,
+    pub normal: Vector3,
+    pub uv: Vector2,
+    pub color: Color
+}
+
+impl Default for Vertex {
+    fn default() -> Vertex {
+        Vertex {
+            position: Vector3::new(0.0, 0.0, 0.0),
+            normal: Vector3::new(0.0, 0.0, 0.0),
+            uv: Vector2::new(0.0, 0.0),
+            color: Color::WHITE
+        }
+    }
+}
+
+impl Vertex {
+    pub fn new(position: Vector3, normal: Vector3, uv: Vector2, color: Color) -> Vertex {
+        Vertex {
+            position,
+            normal,
+            uv,
+            color
+        }
+    }
+
+    // A white vertex with no normal or texture coordinates.
+    pub fn fromPosition(position: Vector3) -> Vertex {
+        Vertex {
+            position,
+            ..Default::default()
+        }
+    }
+}
+
+// Indexed triangle list: every three entries of the index buffer name the
+// vertices of one triangle.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>
+}
+
+impl Mesh {
+    pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>) -> Mesh {
+        Mesh {
+            vertices,
+            indices
+        }
+    }
+
+    pub fn getVertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    pub fn getVerticesMut(&mut self) -> &mut Vec<Vertex> {
+        &mut self.vertices
+    }
+
+    pub fn getIndices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    pub fn getIndicesMut(&mut self) -> &mut Vec<u32> {
+        &mut self.indices
+    }
+
+    // Appends a vertex and returns its index.
+    pub fn addVertex(&mut self, vertex: Vertex) -> u32 {
+        self.vertices.push(vertex);
+        (self.vertices.len() - 1) as u32
+    }
+
+    pub fn addTriangle(&mut self, a: u32, b: u32, c: u32) {
+        self.indices.extend_from_slice(&[a, b, c]);
+    }
+
+    // Trailing indices that do not make up a whole triangle are ignored.
+    pub fn getTriangleCount(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn getTriangle(&self, i: usize) -> Option<[u32; 3]> {
+        if i >= self.getTriangleCount() {
+            return None;
+        }
+
+        Some([self.indices[i * 3], self.indices[i * 3 + 1], self.indices[i * 3 + 2]])
+    }
+
+    pub fn triangles(&self) -> impl Iterator<Item = [u32; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]])
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+use crate::vectors::{VectorProperties::*, Vector2::*, Vector3::*};
+use crate::rasterizer::Color::*;
+use crate::mesh::Mesh::*;
+use crate::defs::PI;
+
+// A point on the outline swept around the y axis by `revolve`, with the
+// outline's direction of travel at that point.
+struct ProfilePoint {
+    radius: f64,
+    y: f64,
+    tangent: Vector2
+}
+
+// Procedural meshes centered on the origin with y up. Triangles wind
+// counter-clockwise seen from outside, like the rest of the engine expects,
+// and tessellation counts below the smallest sensible value are raised to it.
+pub struct Primitives;
+
+impl Primitives {
+    // Cube with `subdivisions` quads along each edge of every face. Faces do
+    // not share vertices, so edges stay sharp.
+    pub fn cube(size: f64, subdivisions: usize) -> Mesh {
+        let mut mesh = Mesh::default();
+        let h = size * 0.5;
+
+        let faces = [
+            (Vector3::new(h, 0.0, 0.0), Vector3::new(0.0, 0.0, -size), Vector3::new(0.0, size, 0.0)),
+            (Vector3::new(-h, 0.0, 0.0), Vector3::new(0.0, 0.0, size), Vector3::new(0.0, size, 0.0)),
+            (Vector3::new(0.0, h, 0.0), Vector3::new(size, 0.0, 0.0), Vector3::new(0.0, 0.0, -size)),
+            (Vector3::new(0.0, -h, 0.0), Vector3::new(size, 0.0, 0.0), Vector3::new(0.0, 0.0, size)),
+            (Vector3::new(0.0, 0.0, h), Vector3::new(size, 0.0, 0.0), Vector3::new(0.0, size, 0.0)),
+            (Vector3::new(0.0, 0.0, -h), Vector3::new(-size, 0.0, 0.0), Vector3::new(0.0, size, 0.0))
+        ];
+
+        for (center, u, v) in faces.iter() {
+            addGrid(&mut mesh, *center, *u, *v, subdivisions.max(1), subdivisions.max(1));
+        }
+
+        mesh
+    }
+
+    // Flat grid in the xz plane facing +y.
+    pub fn plane(width: f64, depth: f64, columns: usize, rows: usize) -> Mesh {
+        let mut mesh = Mesh::default();
+        addGrid(&mut mesh, Vector3::new(0.0, 0.0, 0.0), Vector3::new(width, 0.0, 0.0), Vector3::new(0.0, 0.0, -depth), columns.max(1), rows.max(1));
+        mesh
+    }
+
+    // Sphere of `segments` slices around y and `rings` bands from pole to pole.
+    pub fn uvSphere(radius: f64, segments: usize, rings: usize) -> Mesh {
+        let mut mesh = Mesh::default();
+        let rings = rings.max(2);
+
+        let profile: Vec<ProfilePoint> = (0..=rings)
+            .map(|i| arc(radius, 0.0, PI * i as f64 / rings as f64))
+            .collect();
+
+        revolve(&mut mesh, &profile, segments.max(3));
+        mesh
+    }
+
+    // Sphere from a subdivided icosahedron, with evenly sized triangles and
+    // no poles. Vertices on the texture seam are duplicated so u wraps
+    // cleanly past 1.
+    pub fn icosphere(radius: f64, subdivisions: usize) -> Mesh {
+        let t = (1.0 + 5f64.sqrt()) * 0.5;
+
+        let mut points: Vec<Vector3> = [
+            (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+            (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+            (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0)
+        ].iter().map(|(x, y, z)| Vector3::getNormalized(&Vector3::new(*x, *y, *z))).collect();
+
+        let mut faces: Vec<[usize; 3]> = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1]
+        ];
+
+        for _ in 0..subdivisions {
+            let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+            let mut midpoint = |a: usize, b: usize, points: &mut Vec<Vector3>| -> usize {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    points.push(Vector3::getNormalized(&(points[a] + points[b])));
+                    points.len() - 1
+                })
+            };
+
+            faces = faces.iter().flat_map(|[a, b, c]| {
+                let ab = midpoint(*a, *b, &mut points);
+                let bc = midpoint(*b, *c, &mut points);
+                let ca = midpoint(*c, *a, &mut points);
+                vec![[*a, ab, ca], [*b, bc, ab], [*c, ca, bc], [ab, bc, ca]]
+            }).collect();
+        }
+
+        let mut mesh = Mesh::default();
+
+        for p in points.iter() {
+            let u = p.z.atan2(p.x) / (2.0 * PI);
+            let uv = Vector2::new(if u < 0.0 { u + 1.0 } else { u }, 0.5 + p.y.clamp(-1.0, 1.0).asin() / PI);
+            mesh.addVertex(Vertex::new(scaled(p, radius), *p, uv, Color::WHITE));
+        }
+
+        let mut wrapped: HashMap<u32, u32> = HashMap::new();
+
+        for face in faces.iter() {
+            let mut corners = [face[0] as u32, face[1] as u32, face[2] as u32];
+            let us: Vec<f64> = corners.iter().map(|i| mesh.getVertices()[*i as usize].uv.x).collect();
+
+            // A triangle straddling the seam has corners near u = 0 and u = 1;
+            // move the low ones to copies past 1.
+            if us.iter().cloned().fold(0.0, f64::max) - us.iter().cloned().fold(1.0, f64::min) > 0.5 {
+                for (corner, u) in corners.iter_mut().zip(us.iter()) {
+                    if *u < 0.5 {
+                        *corner = *wrapped.entry(*corner).or_insert_with(|| {
+                            let mut copy = mesh.getVertices()[*corner as usize];
+                            copy.uv.x += 1.0;
+                            mesh.addVertex(copy)
+                        });
+                    }
+                }
+            }
+
+            addOriented(&mut mesh, corners[0], corners[1], corners[2]);
+        }
+
+        mesh
+    }
+
+    // Capped cylinder of `height` along y, with `stacks` bands up its side.
+    pub fn cylinder(radius: f64, height: f64, segments: usize, stacks: usize) -> Mesh {
+        let mut mesh = Mesh::default();
+        let segments = segments.max(3);
+        let stacks = stacks.max(1);
+        let h = height * 0.5;
+
+        let profile: Vec<ProfilePoint> = (0..=stacks)
+            .map(|i| ProfilePoint {
+                radius,
+                y: h - height * i as f64 / stacks as f64,
+                tangent: Vector2::new(0.0, -1.0)
+            })
+            .collect();
+
+        revolve(&mut mesh, &profile, segments);
+        addDisk(&mut mesh, h, radius, segments, true);
+        addDisk(&mut mesh, -h, radius, segments, false);
+        mesh
+    }
+
+    // Cone with its apex at +y and a capped base at -y. Each slice has its own
+    // apex vertex so the side normals stay smooth around the tip.
+    pub fn cone(radius: f64, height: f64, segments: usize, stacks: usize) -> Mesh {
+        let mut mesh = Mesh::default();
+        let segments = segments.max(3);
+        let stacks = stacks.max(1);
+        let h = height * 0.5;
+
+        let profile: Vec<ProfilePoint> = (0..=stacks)
+            .map(|i| {
+                let t = i as f64 / stacks as f64;
+
+                ProfilePoint {
+                    radius: radius * t,
+                    y: h - height * t,
+                    tangent: Vector2::new(radius, -height)
+                }
+            })
+            .collect();
+
+        revolve(&mut mesh, &profile, segments);
+        addDisk(&mut mesh, -h, radius, segments, false);
+        mesh
+    }
+
+    // Torus around the y axis. `segments` run around the ring and `sides`
+    // around the tube.
+    pub fn torus(majorRadius: f64, minorRadius: f64, segments: usize, sides: usize) -> Mesh {
+        let mut mesh = Mesh::default();
+        let sides = sides.max(3);
+
+        // Start on the outer equator heading down so the sweep faces out.
+        let profile: Vec<ProfilePoint> = (0..=sides)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / sides as f64;
+
+                ProfilePoint {
+                    radius: majorRadius + minorRadius * angle.cos(),
+                    y: -minorRadius * angle.sin(),
+                    tangent: Vector2::new(-angle.sin(), -angle.cos())
+                }
+            })
+            .collect();
+
+        revolve(&mut mesh, &profile, segments.max(3));
+        mesh
+    }
+
+    // Cylinder of `height` between two hemispheres, so the overall length is
+    // height + 2 * radius. `rings` bands make up each hemisphere.
+    pub fn capsule(radius: f64, height: f64, segments: usize, rings: usize) -> Mesh {
+        let mut mesh = Mesh::default();
+        let rings = rings.max(1);
+        let h = height * 0.5;
+
+        let top = (0..=rings).map(|i| arc(radius, h, 0.5 * PI * i as f64 / rings as f64));
+        let bottom = (0..=rings).map(|i| arc(radius, -h, 0.5 * PI * (1.0 + i as f64 / rings as f64)));
+        let profile: Vec<ProfilePoint> = top.chain(bottom).collect();
+
+        revolve(&mut mesh, &profile, segments.max(3));
+        mesh
+    }
+}
+
+fn scaled(v: &Vector3, factor: f64) -> Vector3 {
+    Vector3::new(v.x * factor, v.y * factor, v.z * factor)
+}
+
+// A point `angle` radians down from the top of a circle centered at `y`.
+fn arc(radius: f64, y: f64, angle: f64) -> ProfilePoint {
+    ProfilePoint {
+        radius: radius * angle.sin(),
+        y: y + radius * angle.cos(),
+        tangent: Vector2::new(angle.cos(), -angle.sin())
+    }
+}
+
+// Adds a triangle wound counter-clockwise as seen from the side its vertex
+// normals face. Triangles with no area, as at poles and apexes, are dropped.
+fn addOriented(mesh: &mut Mesh, a: u32, b: u32, c: u32) {
+    let v = mesh.getVertices();
+    let (va, vb, vc) = (&v[a as usize], &v[b as usize], &v[c as usize]);
+    let face = Vector3::cross(&(vb.position - va.position), &(vc.position - va.position));
+
+    if Vector3::length(&face) <= 1e-12 {
+        return;
+    }
+
+    if Vector3::dot(&face, &(va.normal + vb.normal + vc.normal)) < 0.0 {
+        mesh.addTriangle(a, c, b);
+    } else {
+        mesh.addTriangle(a, b, c);
+    }
+}
+
+// A flat grid spanning `u` and `v` around `center`, facing u x v.
+fn addGrid(mesh: &mut Mesh, center: Vector3, u: Vector3, v: Vector3, columns: usize, rows: usize) {
+    let normal = Vector3::getNormalized(&Vector3::cross(&u, &v));
+    let first = mesh.getVertices().len() as u32;
+
+    for i in 0..=rows {
+        for j in 0..=columns {
+            let s = j as f64 / columns as f64;
+            let t = i as f64 / rows as f64;
+            let position = center + scaled(&u, s - 0.5) + scaled(&v, t - 0.5);
+
+            mesh.addVertex(Vertex::new(position, normal, Vector2::new(s, t), Color::WHITE));
+        }
+    }
+
+    addQuads(mesh, first, columns, rows);
+}
+
+// Triangulates a (rows + 1) x (columns + 1) block of vertices starting at
+// `first`, laid out row by row.
+fn addQuads(mesh: &mut Mesh, first: u32, columns: usize, rows: usize) {
+    let stride = columns as u32 + 1;
+
+    for i in 0..rows as u32 {
+        for j in 0..columns as u32 {
+            let a = first + i * stride + j;
+            let b = a + 1;
+            let c = a + stride;
+            let d = c + 1;
+
+            addOriented(mesh, a, b, c);
+            addOriented(mesh, b, d, c);
+        }
+    }
+}
+
+// Sweeps the profile once around the y axis. Normals are the cross product
+// of the sweep direction and the profile tangent, which stays well defined
+// where the profile touches the axis. v runs from 1 at the start of the
+// profile to 0 at its end, by arc length.
+fn revolve(mesh: &mut Mesh, profile: &[ProfilePoint], segments: usize) {
+    let mut lengths: Vec<f64> = vec![0.0];
+
+    for pair in profile.windows(2) {
+        let step = ((pair[1].radius - pair[0].radius).powi(2) + (pair[1].y - pair[0].y).powi(2)).sqrt();
+        lengths.push(lengths.last().unwrap() + step);
+    }
+
+    let total = lengths.last().copied().filter(|l| *l > 0.0).unwrap_or(1.0);
+    let first = mesh.getVertices().len() as u32;
+
+    for (point, length) in profile.iter().zip(lengths.iter()) {
+        for j in 0..=segments {
+            let angle = 2.0 * PI * j as f64 / segments as f64;
+            let out = Vector3::new(angle.cos(), 0.0, angle.sin());
+            let around = Vector3::new(-angle.sin(), 0.0, angle.cos());
+            let tangent = scaled(&out, point.tangent.x) + Vector3::new(0.0, point.tangent.y, 0.0);
+
+            mesh.addVertex(Vertex::new(
+                scaled(&out, point.radius) + Vector3::new(0.0, point.y, 0.0),
+                Vector3::getNormalized(&Vector3::cross(&around, &tangent)),
+                Vector2::new(j as f64 / segments as f64, 1.0 - length / total),
+                Color::WHITE));
+        }
+    }
+
+    addQuads(mesh, first, segments, profile.len() - 1);
+}
+
+// A flat cap at height `y` facing up or down.
+fn addDisk(mesh: &mut Mesh, y: f64, radius: f64, segments: usize, up: bool) {
+    let x = Vector3::new(1.0, 0.0, 0.0);
+    let z = Vector3::new(0.0, 0.0, if up { 1.0 } else { -1.0 });
+    let normal = Vector3::cross(&z, &x);
+
+    let center = mesh.addVertex(Vertex::new(Vector3::new(0.0, y, 0.0), normal, Vector2::new(0.5, 0.5), Color::WHITE));
+
+    for j in 0..=segments {
+        let angle = 2.0 * PI * j as f64 / segments as f64;
+        let (c, s) = (angle.cos(), angle.sin());
+
+        mesh.addVertex(Vertex::new(
+            Vector3::new(radius * c, y, radius * s),
+            normal,
+            Vector2::new(0.5 + 0.5 * c, 0.5 + 0.5 * s),
+            Color::WHITE));
+    }
+
+    for j in 0..segments as u32 {
+        addOriented(mesh, center, center + 1 + j, center + 2 + j);
+    }
+}
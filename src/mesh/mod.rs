@@ -0,0 +1,3 @@
+pub mod Mesh;
+pub mod Material;
+pub mod Primitives;
@@ -0,0 +1,159 @@
+use crate::vectors::{VectorProperties::*, Vector4::*};
+use crate::math_utils::GeneralMath;
+
+// A clip-space vertex with any number of attributes that get interpolated
+// alongside the position when an edge is split.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipVertex {
+    pub position: Vector4,
+    pub varyings: Vec<f64>
+}
+
+impl ClipVertex {
+    pub fn new(position: Vector4, varyings: Vec<f64>) -> ClipVertex {
+        ClipVertex {
+            position,
+            varyings
+        }
+    }
+
+    pub fn lerp(a: &ClipVertex, b: &ClipVertex, t: f64) -> ClipVertex {
+        ClipVertex {
+            position: Vector4::lerp(&a.position, &b.position, t),
+            varyings: a.varyings.iter()
+                .zip(b.varyings.iter())
+                .map(|(va, vb)| GeneralMath::interpolate(*va, *vb, t))
+                .collect()
+        }
+    }
+}
+
+// The six planes of the canonical view volume -w <= x, y, z <= w.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ClipPlane {
+    Left,
+    Right,
+    Bottom,
+    Top,
+    Near,
+    Far
+}
+
+impl ClipPlane {
+    pub const ALL: [ClipPlane; 6] = [
+        ClipPlane::Near,
+        ClipPlane::Far,
+        ClipPlane::Left,
+        ClipPlane::Right,
+        ClipPlane::Bottom,
+        ClipPlane::Top
+    ];
+
+    // Signed distance to the plane; positive is inside.
+    pub fn distance(&self, v: &Vector4) -> f64 {
+        match self {
+            ClipPlane::Left => v.w + v.x,
+            ClipPlane::Right => v.w - v.x,
+            ClipPlane::Bottom => v.w + v.y,
+            ClipPlane::Top => v.w - v.y,
+            ClipPlane::Near => v.w + v.z,
+            ClipPlane::Far => v.w - v.z
+        }
+    }
+}
+
+pub struct Clipper;
+
+impl Clipper {
+    // One Sutherland-Hodgman pass of a convex polygon against a single plane.
+    pub fn clipAgainstPlane(polygon: &[ClipVertex], plane: ClipPlane) -> Vec<ClipVertex> {
+        let mut result: Vec<ClipVertex> = Vec::with_capacity(polygon.len() + 1);
+
+        for i in 0..polygon.len() {
+            let current = &polygon[i];
+            let next = &polygon[(i + 1) % polygon.len()];
+
+            let dCurrent: f64 = plane.distance(&current.position);
+            let dNext: f64 = plane.distance(&next.position);
+
+            if dCurrent >= 0.0 {
+                result.push(current.clone());
+            }
+
+            if (dCurrent >= 0.0) != (dNext >= 0.0) {
+                let t: f64 = dCurrent / (dCurrent - dNext);
+                result.push(ClipVertex::lerp(current, next, t));
+            }
+        }
+
+        result
+    }
+
+    pub fn clipPolygon(polygon: &[ClipVertex]) -> Vec<ClipVertex> {
+        let mut result: Vec<ClipVertex> = polygon.to_vec();
+
+        for plane in ClipPlane::ALL.iter() {
+            if result.len() < 3 {
+                return Vec::new();
+            }
+
+            result = Clipper::clipAgainstPlane(&result, *plane);
+        }
+
+        if result.len() < 3 {
+            return Vec::new();
+        }
+
+        result
+    }
+
+    pub fn isInside(position: &Vector4) -> bool {
+        ClipPlane::ALL.iter().all(|plane| plane.distance(position) >= 0.0)
+    }
+
+    // Clips a line segment against the view volume, returning what is left
+    // of it, if anything.
+    pub fn clipLine(a: &ClipVertex, b: &ClipVertex) -> Option<[ClipVertex; 2]> {
+        let mut tStart: f64 = 0.0;
+        let mut tEnd: f64 = 1.0;
+
+        for plane in ClipPlane::ALL.iter() {
+            let dA: f64 = plane.distance(&a.position);
+            let dB: f64 = plane.distance(&b.position);
+
+            if dA < 0.0 && dB < 0.0 {
+                return None;
+            }
+
+            if dA < 0.0 {
+                tStart = tStart.max(dA / (dA - dB));
+            } else if dB < 0.0 {
+                tEnd = tEnd.min(dA / (dA - dB));
+            }
+
+            if tStart > tEnd {
+                return None;
+            }
+        }
+
+        Some([ClipVertex::lerp(a, b, tStart), ClipVertex::lerp(a, b, tEnd)])
+    }
+
+    // Clips a triangle against the view volume and fans the resulting
+    // polygon back into triangles sharing the first vertex.
+    pub fn clipTriangle(v1: &ClipVertex, v2: &ClipVertex, v3: &ClipVertex) -> Vec<[ClipVertex; 3]> {
+        let inside = Clipper::isInside(&v1.position) &&
+            Clipper::isInside(&v2.position) &&
+            Clipper::isInside(&v3.position);
+
+        if inside {
+            return vec![[v1.clone(), v2.clone(), v3.clone()]];
+        }
+
+        let polygon = Clipper::clipPolygon(&[v1.clone(), v2.clone(), v3.clone()]);
+
+        (1..polygon.len().saturating_sub(1))
+            .map(|i| [polygon[0].clone(), polygon[i].clone(), polygon[i + 1].clone()])
+            .collect()
+    }
+}
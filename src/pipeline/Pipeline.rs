@@ -0,0 +1,186 @@
+use crate::matrices::Matrix44::*;
+use crate::vectors::{Vector3::*, Vector4::*};
+use crate::rasterizer::{FrameBuffer::*, Fragment::*};
+use crate::pipeline::Clipper::*;
+
+// Region of the framebuffer that normalized device coordinates map onto.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64
+}
+
+impl Viewport {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Viewport {
+        Viewport {
+            x,
+            y,
+            width,
+            height
+        }
+    }
+
+    pub fn fromFrameBuffer(fb: &FrameBuffer) -> Viewport {
+        Viewport::new(0.0, 0.0, fb.getWidth() as f64, fb.getHeight() as f64)
+    }
+
+    // Perspective divide followed by the viewport transform. The result is
+    // in framebuffer cells with y pointing down and depth in [0, 1].
+    pub fn toScreenSpace(&self, clip: &Vector4) -> Vector3 {
+        let invW: f64 = 1.0 / clip.w;
+
+        let ndcX: f64 = clip.x * invW;
+        let ndcY: f64 = clip.y * invW;
+        let ndcZ: f64 = clip.z * invW;
+
+        Vector3 {
+            x: self.x + (ndcX + 1.0) * 0.5 * self.width,
+            y: self.y + (1.0 - ndcY) * 0.5 * self.height,
+            z: (ndcZ + 1.0) * 0.5
+        }
+    }
+
+    // Like toScreenSpace, keeping what the rasterizer needs to interpolate
+    // the vertex's varyings.
+    pub fn toScreenVertex(&self, clip: &ClipVertex) -> ScreenVertex {
+        ScreenVertex::new(self.toScreenSpace(&clip.position), 1.0 / clip.position.w, clip.varyings.clone())
+    }
+}
+
+// Carries vertices from model space through view and projection into clip
+// space, then through the perspective divide onto the viewport.
+pub struct Pipeline {
+    model: Matrix44,
+    view: Matrix44,
+    projection: Matrix44,
+    modelViewProjection: Matrix44,
+    viewport: Viewport
+}
+
+impl Pipeline {
+    pub fn new(viewport: Viewport) -> Pipeline {
+        Pipeline {
+            model: Default::default(),
+            view: Default::default(),
+            projection: Default::default(),
+            modelViewProjection: Default::default(),
+            viewport
+        }
+    }
+
+    pub fn getModel(&self) -> &Matrix44 {
+        &self.model
+    }
+
+    pub fn setModel(&mut self, model: &Matrix44) {
+        self.model = *model;
+        self.update();
+    }
+
+    pub fn getView(&self) -> &Matrix44 {
+        &self.view
+    }
+
+    pub fn setView(&mut self, view: &Matrix44) {
+        self.view = *view;
+        self.update();
+    }
+
+    pub fn getProjection(&self) -> &Matrix44 {
+        &self.projection
+    }
+
+    pub fn setProjection(&mut self, projection: &Matrix44) {
+        self.projection = *projection;
+        self.update();
+    }
+
+    pub fn getModelViewProjection(&self) -> &Matrix44 {
+        &self.modelViewProjection
+    }
+
+    pub fn getViewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    pub fn setViewport(&mut self, viewport: &Viewport) {
+        self.viewport = *viewport;
+    }
+
+    fn update(&mut self) {
+        self.modelViewProjection = self.projection * self.view * self.model;
+    }
+
+    pub fn toClipSpace(&self, v: &Vector4) -> Vector4 {
+        self.modelViewProjection * *v
+    }
+
+    pub fn toScreenSpace(&self, clip: &Vector4) -> Vector3 {
+        self.viewport.toScreenSpace(clip)
+    }
+
+    pub fn processVertex(&self, v: &Vector4) -> Vector3 {
+        self.toScreenSpace(&self.toClipSpace(v))
+    }
+
+    pub fn toScreenVertex(&self, clip: &ClipVertex) -> ScreenVertex {
+        self.viewport.toScreenVertex(clip)
+    }
+
+    // Transforms a model-space triangle, clips it against the view volume
+    // and returns the screen-space triangles that remain.
+    pub fn processTriangle(&self, v1: &Vector4, v2: &Vector4, v3: &Vector4) -> Vec<[Vector3; 3]> {
+        let c1 = ClipVertex::new(self.toClipSpace(v1), Vec::new());
+        let c2 = ClipVertex::new(self.toClipSpace(v2), Vec::new());
+        let c3 = ClipVertex::new(self.toClipSpace(v3), Vec::new());
+
+        Clipper::clipTriangle(&c1, &c2, &c3).iter()
+            .map(|[a, b, c]| {
+                [self.toScreenSpace(&a.position), self.toScreenSpace(&b.position), self.toScreenSpace(&c.position)]
+            })
+            .collect()
+    }
+
+    // Clips a triangle already in clip space, splitting its varyings at the
+    // clip planes, and projects what remains for rasterizeVaryingTriangle.
+    pub fn processClipTriangle(&self, v1: &ClipVertex, v2: &ClipVertex, v3: &ClipVertex) -> Vec<[ScreenVertex; 3]> {
+        Clipper::clipTriangle(v1, v2, v3).iter()
+            .map(|[a, b, c]| [self.toScreenVertex(a), self.toScreenVertex(b), self.toScreenVertex(c)])
+            .collect()
+    }
+
+    // Like processTriangle, with per-vertex varyings carried through.
+    pub fn processVaryingTriangle(&self, v1: &Vector4, v2: &Vector4, v3: &Vector4, varyings: [&[f64]; 3]) -> Vec<[ScreenVertex; 3]> {
+        self.processClipTriangle(
+            &ClipVertex::new(self.toClipSpace(v1), varyings[0].to_vec()),
+            &ClipVertex::new(self.toClipSpace(v2), varyings[1].to_vec()),
+            &ClipVertex::new(self.toClipSpace(v3), varyings[2].to_vec()))
+    }
+
+    // Clips a model-space line segment and projects what remains.
+    pub fn processLine(&self, v1: &Vector4, v2: &Vector4) -> Option<[Vector3; 2]> {
+        self.processVaryingLine(v1, v2, [&[], &[]])
+            .map(|[a, b]| [a.position, b.position])
+    }
+
+    pub fn processVaryingLine(&self, v1: &Vector4, v2: &Vector4, varyings: [&[f64]; 2]) -> Option<[ScreenVertex; 2]> {
+        let c1 = ClipVertex::new(self.toClipSpace(v1), varyings[0].to_vec());
+        let c2 = ClipVertex::new(self.toClipSpace(v2), varyings[1].to_vec());
+
+        Clipper::clipLine(&c1, &c2)
+            .map(|[a, b]| [self.toScreenVertex(&a), self.toScreenVertex(&b)])
+    }
+
+    // Projects a model-space point, or None if it lies outside the view volume.
+    pub fn processPoint(&self, v: &Vector4) -> Option<Vector3> {
+        let clip = self.toClipSpace(v);
+
+        if !Clipper::isInside(&clip) {
+            return None;
+        }
+
+        Some(self.toScreenSpace(&clip))
+    }
+}
@@ -0,0 +1,2 @@
+pub mod Pipeline;
+pub mod Clipper;
@@ -2,12 +2,14 @@ use crate::vectors::{VectorProperties::* ,Vector3::Vector3, Vector4::*};
 use crate::matrices::{Matrix44::*};
 use std::ops;
 
+// Rotation quaternion. Matrices produced and consumed here use the same
+// column-major layout as Matrix44::rotate, so both rotation paths agree.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Quaternion {
-    x: f64,
-    y: f64,
-    z: f64,
-    w: f64
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64
 }
 
 impl Default for Quaternion {
@@ -22,18 +24,56 @@ impl Default for Quaternion {
 }
 
 impl Quaternion {
-    fn setIdentity(&mut self) {
+    pub fn new(x: f64, y: f64, z: f64, w: f64) -> Quaternion {
+        Quaternion {
+            x,
+            y,
+            z,
+            w
+        }
+    }
+
+    // Rotation of `angle` radians about `axis`, which need not be normalized.
+    pub fn fromAxisAngle(axis: &Vector3, angle: f64) -> Quaternion {
+        let mut q: Quaternion = Default::default();
+        q.setToAxisAngle(axis, angle);
+        q
+    }
+
+    // Euler angles in radians: `pitch` about x, `yaw` about y and `roll`
+    // about z. Roll is applied first, then pitch, then yaw.
+    pub fn fromEuler(pitch: f64, yaw: f64, roll: f64) -> Quaternion {
+        let qx = Quaternion::fromAxisAngle(&Vector3::new(1.0, 0.0, 0.0), pitch);
+        let qy = Quaternion::fromAxisAngle(&Vector3::new(0.0, 1.0, 0.0), yaw);
+        let qz = Quaternion::fromAxisAngle(&Vector3::new(0.0, 0.0, 1.0), roll);
+
+        qy * qx * qz
+    }
+
+    // Reads the rotation from the upper 3x3 block of `matrix`.
+    pub fn fromMatrix(matrix: &Matrix44) -> Quaternion {
+        let mut q: Quaternion = Default::default();
+        q.setMatrix(matrix);
+        q.normalize();
+        q
+    }
+
+    pub fn setIdentity(&mut self) {
         self.x = 0.0;
         self.y = 0.0;
         self.z = 0.0;
         self.w = 1.0;
     }
 
-    fn length(&self) -> f64 {
+    pub fn length(&self) -> f64 {
         (self.x*self.x + self.y*self.y + self.z*self.z + self.w*self.w).sqrt()
     }
 
-    fn normalize(&mut self) {
+    pub fn dot(a: &Quaternion, b: &Quaternion) -> f64 {
+        a.w*b.w + a.x*b.x + a.y*b.y + a.z*b.z
+    }
+
+    pub fn normalize(&mut self) {
         let mag: f64 = self.length();
 
         if mag == 0f64 {
@@ -46,16 +86,47 @@ impl Quaternion {
         self.w /= mag;
     }
 
-    fn negate(&mut self) {
+    pub fn getNormalized(q: &Quaternion) -> Quaternion {
+        let mut f: Quaternion = *q;
+        f.normalize();
+        f
+    }
+
+    pub fn negate(&mut self) {
         self.x = -self.x;
         self.y = -self.y;
         self.z = -self.z;
         self.w = -self.w;
     }
 
-    fn toMatrix(&self) -> Matrix44 {
-        let mut matrix: Matrix44 = Default::default();
+    pub fn conjugate(&self) -> Quaternion {
+        Quaternion {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+            w: self.w
+        }
+    }
+
+    // Multiplicative inverse. For unit quaternions this is the conjugate.
+    pub fn inverse(&self) -> Quaternion {
+        let lengthSq: f64 = Quaternion::dot(self, self);
+
+        if lengthSq == 0f64 {
+            return *self;
+        }
 
+        let c = self.conjugate();
+
+        Quaternion {
+            x: c.x / lengthSq,
+            y: c.y / lengthSq,
+            z: c.z / lengthSq,
+            w: c.w / lengthSq
+        }
+    }
+
+    pub fn toMatrix(&self) -> Matrix44 {
         let xy: f64 = self.x * self.y;
         let xz: f64 = self.x * self.z;
         let xw: f64 = self.x * self.w;
@@ -63,39 +134,55 @@ impl Quaternion {
         let yw: f64 = self.y * self.w;
         let zw: f64 = self.z * self.w;
 
-        matrix.m00 = 1.0 - 2.0 * (self.y*self.y + self.z*self.z);
-        matrix.m01 = 2.0 * (xy - zw);
-        matrix.m02 = 2.0 * (xz + yw);
-        matrix.m03 = 0.0;
-        matrix.m10 = 2.0 * (xy + zw);
-        matrix.m11 = 1.0 - 2.0 * (self.x*self.x + self.z*self.z);
-        matrix.m12 = 2.0 * (yz - xw);
-        matrix.m13 = 0.0;
-        matrix.m20 = 2.0 * (xz - yw);
-        matrix.m21 = 2.0 * (yz + xw);
-        matrix.m22 = 1.0 - 2.0 * (self.x*self.x + self.y*self.y);
-        matrix.m23 = 0.0;
-        matrix.m30 = 0.0;
-        matrix.m31 = 0.0;
-        matrix.m32 = 0.0;
-        matrix.m33 = 1.0;
-
-        return matrix;
-    }
-
-    fn setToAxisAngle(&mut self, axis: &Vector3, angle: f64) {
-        let mut rot: Matrix44 = Default::default();
-
-        rot.rotate(&axis, angle);
-        
-        self.setMatrix(&rot);
-        self.normalize();
+        Matrix44 {
+            m00: 1.0 - 2.0 * (self.y*self.y + self.z*self.z),
+            m01: 2.0 * (xy + zw),
+            m02: 2.0 * (xz - yw),
+            m03: 0.0,
+            m10: 2.0 * (xy - zw),
+            m11: 1.0 - 2.0 * (self.x*self.x + self.z*self.z),
+            m12: 2.0 * (yz + xw),
+            m13: 0.0,
+            m20: 2.0 * (xz + yw),
+            m21: 2.0 * (yz - xw),
+            m22: 1.0 - 2.0 * (self.x*self.x + self.y*self.y),
+            m23: 0.0,
+            m30: 0.0,
+            m31: 0.0,
+            m32: 0.0,
+            m33: 1.0
+        }
+    }
+
+    pub fn setToAxisAngle(&mut self, axis: &Vector3, angle: f64) {
+        let n: Vector3 = Vector3::getNormalized(axis);
+        let s: f64 = (angle * 0.5).sin();
+
+        self.x = n.x * s;
+        self.y = n.y * s;
+        self.z = n.z * s;
+        self.w = (angle * 0.5).cos();
+    }
+
+    // Rotates `v` by this quaternion, which is assumed to be unit length.
+    pub fn rotate(&self, v: &Vector3) -> Vector3 {
+        let q: Vector3 = Vector3::new(self.x, self.y, self.z);
+
+        let mut t: Vector3 = Vector3::cross(&q, v);
+        t.scale(2.0);
+
+        let mut wt: Vector3 = t;
+        wt.scale(self.w);
+
+        *v + wt + Vector3::cross(&q, &t)
     }
 
-    fn slerp(a: &Quaternion, b: &Quaternion, blend: f64) -> Quaternion {
+    // Normalized linear interpolation along the shorter arc. Cheaper than
+    // slerp but does not move at constant angular speed.
+    pub fn nlerp(a: &Quaternion, b: &Quaternion, blend: f64) -> Quaternion {
         let mut result: Quaternion = Default::default();
 
-        let dot = a.w *b.w + a.x*b.x + a.y*b.y + a.z*b.z;
+        let dot = Quaternion::dot(a, b);
         let blendI = 1.0 - blend;
 
         if dot < 0.0 {
@@ -111,83 +198,117 @@ impl Quaternion {
         }
 
         result.normalize();
-        return result;
+        result
     }
 
+    // Spherical linear interpolation along the shorter arc.
+    pub fn slerp(a: &Quaternion, b: &Quaternion, blend: f64) -> Quaternion {
+        let mut dot = Quaternion::dot(a, b);
+        let mut end: Quaternion = *b;
+
+        if dot < 0.0 {
+            dot = -dot;
+            end.negate();
+        }
+
+        // Nearly parallel, the sine below would lose precision.
+        if dot > 0.9995 {
+            return Quaternion::nlerp(a, &end, blend);
+        }
+
+        let theta: f64 = dot.acos();
+        let sinTheta: f64 = theta.sin();
+        let wa: f64 = ((1.0 - blend) * theta).sin() / sinTheta;
+        let wb: f64 = (blend * theta).sin() / sinTheta;
+
+        Quaternion {
+            x: wa * a.x + wb * end.x,
+            y: wa * a.y + wb * end.y,
+            z: wa * a.z + wb * end.z,
+            w: wa * a.w + wb * end.w
+        }
+    }
+
+    // Orientation that turns +z towards `f` with +y as close to `u` as possible.
+    // When `f` runs along `u` another up axis is used, as in Matrix44::lookAt.
     pub fn lookRotation(&mut self, f: &Vector3, u: &Vector3) {
-        // Copy (should work)
-        let mut forward: Vector3 = *f;
-        let mut up: Vector3 = *u;
+        let forward: Vector3 = Vector3::getNormalized(f);
+        let mut side: Vector3 = Vector3::cross(u, &forward);
 
-        forward.normalize();
-        up.normalize();
-        
-        let right = Vector3::cross(&forward, &up);
+        if side.length() < 1e-12 {
+            let fallback = if forward.z.abs() < 0.9 { Vector3::new(0.0, 0.0, -1.0) } else { Vector3::new(0.0, 1.0, 0.0) };
+            side = Vector3::cross(&fallback, &forward);
+        }
 
-        let mut rot: Matrix44 = Default::default();
+        let right: Vector3 = Vector3::getNormalized(&side);
+        let up: Vector3 = Vector3::cross(&forward, &right);
 
-        rot.m00 = right.x;
-        rot.m10 = right.y;
-        rot.m20 = right.z;
+        let rot: Matrix44 = Matrix44 {
+            m00: right.x,
+            m01: right.y,
+            m02: right.z,
 
-        rot.m01 = up.x;
-        rot.m11 = up.y;
-        rot.m21 = up.z;
+            m10: up.x,
+            m11: up.y,
+            m12: up.z,
 
-        rot.m02 = forward.x;
-        rot.m12 = forward.y;
-        rot.m22 = forward.z;
+            m20: forward.x,
+            m21: forward.y,
+            m22: forward.z,
+            ..Default::default()
+        };
 
         self.setMatrix(&rot);
         self.normalize();
     }
 
-    fn setMatrix(&mut self, matrix: &Matrix44) {
-        let m00 = matrix.m00;
-        let m01 = matrix.m01;
-        let m02 = matrix.m02;
+    pub fn setMatrix(&mut self, matrix: &Matrix44) {
+        // Row/column naming of the rotation, Matrix44 stores columns first.
+        let r00 = matrix.m00;
+        let r01 = matrix.m10;
+        let r02 = matrix.m20;
    
-        let m10 = matrix.m10;
-        let m11 = matrix.m11;
-        let m12 = matrix.m12;
+        let r10 = matrix.m01;
+        let r11 = matrix.m11;
+        let r12 = matrix.m21;
    
-        let m20 = matrix.m20;
-        let m21 = matrix.m21;
-        let m22 = matrix.m22;
+        let r20 = matrix.m02;
+        let r21 = matrix.m12;
+        let r22 = matrix.m22;
 
         let mut s: f64;
-        let tr = m00 + m11 + m22;
+        let tr = r00 + r11 + r22;
 
         if tr >= 0.0 {
             s = (tr + 1.0).sqrt();
             self.w = s * 0.5;
             s = 0.5 / s;
-            self.x = (m21 - m12) * s;
-            self.y = (m02 - m20) * s;
-            self.z = (m10 - m01) * s;
+            self.x = (r21 - r12) * s;
+            self.y = (r02 - r20) * s;
+            self.z = (r10 - r01) * s;
         } else {
-            let max: f64 = m00.max(m11).max(m22);
-            if max == m00 {
-                s = (m00 - (m11 + m22) + 1.0).sqrt();
+            let max: f64 = r00.max(r11).max(r22);
+            if max == r00 {
+                s = (r00 - (r11 + r22) + 1.0).sqrt();
                 self.x = s * 0.5;
                 s = 0.5 / s;
-                self.y = (m01 + m10) * s;
-                self.z = (m20 + m02) * s;
-                self.w = (m21 - m12) * s;
-            } else if max == m11 {
-                s = (m11 - (m22 + m00) + 1.0).sqrt();
+                self.y = (r01 + r10) * s;
+                self.z = (r20 + r02) * s;
+                self.w = (r21 - r12) * s;
+            } else if max == r11 {
+                s = (r11 - (r22 + r00) + 1.0).sqrt();
                 self.y = s * 0.5;
                 s = 0.5 / s;
-                self.z = (m12 + m21) * s;
-                self.x = (m01 + m10) * s;
-                self.w = (m02 - m20) * s;
+                self.z = (r12 + r21) * s;
+                self.x = (r01 + r10) * s;
+                self.w = (r02 - r20) * s;
             } else {
-                s = (m22 - (m00 + m11) + 1.0).sqrt();
+                s = (r22 - (r00 + r11) + 1.0).sqrt();
                 self.z = s * 0.5;
                 s = 0.5 / s;
-                self.x = (m20 + m02) * s;
-                self.y = (m12 + m21) * s;
-                self.w = (m10 - m01) * s;
+                self.x = (r20 + r02) * s;
+                self.y = (r12 + r21) * s;
+                self.w = (r10 - r01) * s;
             }
         }
     }
@@ -217,15 +338,7 @@ impl ops::Mul<Vector3> for Quaternion {
     type Output = Vector3;
 
     fn mul(self, rhs: Vector3) -> Vector3 {
-        let trans: Matrix44 = self.toMatrix();
-
-        let r: Vector4 = trans * Vector4{x: rhs.x, y: rhs.y, z: rhs.z, w: 0f64};
-
-        Vector3 {
-            x: r.x,
-            y: r.y,
-            z: r.z
-        }
+        self.rotate(&rhs)
     }
 }
 
@@ -240,9 +353,6 @@ impl ops::Mul<Vector4> for Quaternion {
 
 impl ops::MulAssign<Quaternion> for Quaternion {
     fn mul_assign(&mut self, rhs: Quaternion) {
-        self.x = self.x * rhs.w + self.w * rhs.x + self.y * rhs.z - self.z * rhs.y;
-        self.y = self.y * rhs.w + self.w * rhs.y + self.z * rhs.x - self.x * rhs.z;
-        self.z = self.z * rhs.w + self.w * rhs.z + self.x * rhs.y - self.y * rhs.x;
-        self.w = self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z;
+        *self = *self * rhs;
     }
-}
\ No newline at end of file
+}
@@ -1 +1 @@
-pub mod Quaternion;
\ No newline at end of file
+pub mod Quaternion;
@@ -0,0 +1,79 @@
+use crate::rasterizer::{FrameBuffer::*, RenderTarget::*};
+use std::fmt;
+
+// In-memory render target holding one glyph per cell, for rendering
+// without a terminal. Display prints the rows separated by newlines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharGrid {
+    width: i32,
+    height: i32,
+    glyphs: Vec<char>
+}
+
+impl CharGrid {
+    pub fn new(w: i32, h: i32) -> CharGrid {
+        let w = w.max(0);
+        let h = h.max(0);
+
+        CharGrid {
+            width: w,
+            height: h,
+            glyphs: vec![' '; (w * h) as usize]
+        }
+    }
+
+    pub fn getWidth(&self) -> i32 {
+        self.width
+    }
+
+    pub fn getHeight(&self) -> i32 {
+        self.height
+    }
+
+    pub fn getGlyph(&self, x: i32, y: i32) -> Option<char> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some(self.glyphs[(y * self.width + x) as usize])
+    }
+
+    pub fn getRow(&self, y: i32) -> Option<String> {
+        if y < 0 || y >= self.height {
+            return None;
+        }
+
+        let start = (y * self.width) as usize;
+        Some(self.glyphs[start..start + self.width as usize].iter().collect())
+    }
+}
+
+impl RenderTarget for CharGrid {
+    fn getSize(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    fn putCell(&mut self, x: i32, y: i32, cell: &Cell) {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return;
+        }
+
+        self.glyphs[(y * self.width + x) as usize] = cell.glyph;
+    }
+
+    fn flush(&mut self) {}
+}
+
+impl fmt::Display for CharGrid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.height {
+            if y > 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{}", self.getRow(y).unwrap_or_default())?;
+        }
+
+        Ok(())
+    }
+}
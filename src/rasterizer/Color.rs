@@ -0,0 +1,171 @@
+use std::env;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8
+}
+
+impl Default for Color {
+    fn default() -> Color {
+        Color::WHITE
+    }
+}
+
+// Levels of the 6x6x6 color cube in the xterm 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Color {
+    pub const BLACK: Color = Color { r: 0, g: 0, b: 0 };
+    pub const WHITE: Color = Color { r: 255, g: 255, b: 255 };
+
+    pub fn new(r: u8, g: u8, b: u8) -> Color {
+        Color {
+            r,
+            g,
+            b
+        }
+    }
+
+    // Components in [0, 1], clamped.
+    pub fn fromFloats(r: f64, g: f64, b: f64) -> Color {
+        let channel = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        Color {
+            r: channel(r),
+            g: channel(g),
+            b: channel(b)
+        }
+    }
+
+    pub fn scaled(&self, factor: f64) -> Color {
+        Color::fromFloats(self.r as f64 / 255.0 * factor,
+            self.g as f64 / 255.0 * factor,
+            self.b as f64 / 255.0 * factor)
+    }
+
+    pub fn toFloats(&self) -> [f64; 3] {
+        [self.r as f64 / 255.0, self.g as f64 / 255.0, self.b as f64 / 255.0]
+    }
+
+    // Component-wise product, e.g. a vertex color tinting a base color.
+    pub fn modulate(&self, other: &Color) -> Color {
+        let [r, g, b] = other.toFloats();
+        let [sr, sg, sb] = self.toFloats();
+
+        Color::fromFloats(sr * r, sg * g, sb * b)
+    }
+
+    pub fn lerp(c1: &Color, c2: &Color, t: f64) -> Color {
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round().clamp(0.0, 255.0) as u8;
+
+        Color {
+            r: mix(c1.r, c2.r),
+            g: mix(c1.g, c2.g),
+            b: mix(c1.b, c2.b)
+        }
+    }
+
+    // One of the 8 basic ANSI/curses colors, in their standard order
+    // (black, red, green, yellow, blue, magenta, cyan, white). Brightness is
+    // normalized away first since the glyph already carries it; only
+    // near-black stays black.
+    pub fn toAnsi8(&self) -> u8 {
+        let max = self.r.max(self.g).max(self.b);
+
+        if max < 16 {
+            return 0;
+        }
+
+        let bit = |v: u8| if (v as u32 * 255) / max as u32 >= 128 { 1 } else { 0 };
+
+        bit(self.r) | (bit(self.g) << 1) | (bit(self.b) << 2)
+    }
+
+    // Nearest entry of the xterm 256-color palette, choosing between the
+    // 6x6x6 cube (16-231) and the grayscale ramp (232-255).
+    pub fn toXterm256(&self) -> u8 {
+        let cubeIndex = |v: u8| {
+            (0..CUBE_LEVELS.len())
+                .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - v as i32).abs())
+                .unwrap()
+        };
+
+        let (ri, gi, bi) = (cubeIndex(self.r), cubeIndex(self.g), cubeIndex(self.b));
+        let cube = Color::new(CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+
+        let average = (self.r as i32 + self.g as i32 + self.b as i32) / 3;
+        let grayIndex = ((average - 8).max(0) / 10).min(23);
+        let grayLevel = (8 + grayIndex * 10) as u8;
+        let gray = Color::new(grayLevel, grayLevel, grayLevel);
+
+        if Color::distanceSq(self, &gray) < Color::distanceSq(self, &cube) {
+            232 + grayIndex as u8
+        } else {
+            16 + (36 * ri + 6 * gi + bi) as u8
+        }
+    }
+
+    fn distanceSq(c1: &Color, c2: &Color) -> i32 {
+        let dr = c1.r as i32 - c2.r as i32;
+        let dg = c1.g as i32 - c2.g as i32;
+        let db = c1.b as i32 - c2.b as i32;
+
+        dr * dr + dg * dg + db * db
+    }
+}
+
+// Color capability of a terminal, from least to most capable.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorMode {
+    Monochrome,
+    Ansi8,
+    Xterm256,
+    TrueColor
+}
+
+impl ColorMode {
+    // Guesses the terminal's capability from COLORTERM and TERM.
+    pub fn detect() -> ColorMode {
+        let colorTerm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+
+        ColorMode::fromEnv(&colorTerm, &term)
+    }
+
+    pub fn fromEnv(colorTerm: &str, term: &str) -> ColorMode {
+        if colorTerm == "truecolor" || colorTerm == "24bit" {
+            ColorMode::TrueColor
+        } else if term.contains("256color") {
+            ColorMode::Xterm256
+        } else if term.is_empty() || term == "dumb" {
+            ColorMode::Monochrome
+        } else {
+            ColorMode::Ansi8
+        }
+    }
+
+    // SGR escape sequence selecting `color` as the foreground, quantized to
+    // this mode. Empty for monochrome.
+    pub fn foregroundEscape(&self, color: &Color) -> String {
+        match self {
+            ColorMode::Monochrome => String::new(),
+            ColorMode::Ansi8 => format!("\x1b[{}m", 30 + color.toAnsi8()),
+            ColorMode::Xterm256 => format!("\x1b[38;5;{}m", color.toXterm256()),
+            ColorMode::TrueColor => format!("\x1b[38;2;{};{};{}m", color.r, color.g, color.b)
+        }
+    }
+
+    // SGR escape sequence selecting the background, or the terminal default
+    // background when `color` is None.
+    pub fn backgroundEscape(&self, color: Option<&Color>) -> String {
+        match (self, color) {
+            (ColorMode::Monochrome, _) => String::new(),
+            (_, None) => String::from("\x1b[49m"),
+            (ColorMode::Ansi8, Some(c)) => format!("\x1b[{}m", 40 + c.toAnsi8()),
+            (ColorMode::Xterm256, Some(c)) => format!("\x1b[48;5;{}m", c.toXterm256()),
+            (ColorMode::TrueColor, Some(c)) => format!("\x1b[48;2;{};{};{}m", c.r, c.g, c.b)
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// Screen coordinates are snapped to a fixed-point grid with this many
+// fractional bits before edge setup, so coverage decisions are exact.
+pub const SUBPIXEL_BITS: u32 = 8;
+pub const SUBPIXEL_ONE: i64 = 1 << SUBPIXEL_BITS;
+
+// Largest screen coordinate, in cells, that edge setup accepts. In fixed
+// point the products in `evaluate` then stay well inside an i64, so
+// triangles reaching beyond it are rejected rather than overflowing.
+pub const GUARD_BAND: f64 = (1 << 20) as f64;
+
+pub fn toFixed(v: f64) -> i64 {
+    (v * SUBPIXEL_ONE as f64).round() as i64
+}
+
+// False for points outside the guard band, including NaN.
+pub fn inGuardBand(x: f64, y: f64) -> bool {
+    x.abs() <= GUARD_BAND && y.abs() <= GUARD_BAND
+}
+
+// The edge from a to b as the cross product (b - a) x (p - a), which is
+// linear in p and so can be stepped incrementally across a row. For a
+// triangle with positive area the interior is where all three are positive.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EdgeFunction {
+    ax: i64,
+    ay: i64,
+    dx: i64,
+    dy: i64
+}
+
+impl EdgeFunction {
+    // Endpoints are in fixed point.
+    pub fn new(ax: i64, ay: i64, bx: i64, by: i64) -> EdgeFunction {
+        EdgeFunction {
+            ax,
+            ay,
+            dx: bx - ax,
+            dy: by - ay
+        }
+    }
+
+    pub fn evaluate(&self, px: i64, py: i64) -> i64 {
+        self.dx * (py - self.ay) - self.dy * (px - self.ax)
+    }
+
+    // Change in value when moving one whole cell right or down.
+    pub fn stepX(&self) -> i64 {
+        -self.dy * SUBPIXEL_ONE
+    }
+
+    pub fn stepY(&self) -> i64 {
+        self.dx * SUBPIXEL_ONE
+    }
+
+    // Change in value for a fixed-point offset from a sample point.
+    pub fn offset(&self, ox: i64, oy: i64) -> i64 {
+        self.dx * oy - self.dy * ox
+    }
+
+    // With y pointing down and positive area, a top edge is horizontal with
+    // the interior below it and a left edge has the interior to its right.
+    pub fn isTopLeft(&self) -> bool {
+        (self.dy == 0 && self.dx > 0) || self.dy < 0
+    }
+
+    // Added to a value before testing it against zero, so samples exactly on
+    // an edge belong only to the triangle for which that edge is top or left.
+    pub fn getBias(&self) -> i64 {
+        if self.isTopLeft() { 0 } else { -1 }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::vectors::Vector3::*;
+use crate::rasterizer::Color::*;
+
+// A vertex after the perspective divide. `invW` is 1 / clip-space w, which
+// the rasterizer uses to interpolate `varyings` perspective correctly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenVertex {
+    pub position: Vector3,
+    pub invW: f64,
+    pub varyings: Vec<f64>
+}
+
+impl ScreenVertex {
+    pub fn new(position: Vector3, invW: f64, varyings: Vec<f64>) -> ScreenVertex {
+        ScreenVertex {
+            position,
+            invW,
+            varyings
+        }
+    }
+
+    // A vertex already in screen space with no perspective to correct for.
+    pub fn fromScreen(position: &Vector3, varyings: Vec<f64>) -> ScreenVertex {
+        ScreenVertex::new(*position, 1.0, varyings)
+    }
+}
+
+// A covered cell handed to a fragment callback. With supersampling,
+// `depth` and `varyings` are averaged over the covered samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    pub x: i32,
+    pub y: i32,
+    pub depth: f64,
+    pub coverage: f64,
+    pub varyings: Vec<f64>
+}
+
+// What a fragment callback draws. Without an explicit glyph the rasterizer
+// picks one from its ramp using `intensity`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Shade {
+    pub color: Color,
+    pub intensity: f64,
+    pub glyph: Option<char>
+}
+
+impl Shade {
+    pub fn new(color: Color, intensity: f64) -> Shade {
+        Shade {
+            color,
+            intensity,
+            glyph: None
+        }
+    }
+
+    pub fn withGlyph(glyph: char, color: Color) -> Shade {
+        Shade {
+            color,
+            intensity: 1.0,
+            glyph: Some(glyph)
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use crate::rasterizer::{RenderTarget::*, Color::*};
+
+// A single framebuffer cell. `color` is the glyph's foreground color and
+// `background` the cell's, None leaving the terminal default; render targets
+// quantize both to whatever the terminal supports.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cell {
+    pub glyph: char,
+    pub color: Color,
+    pub background: Option<Color>,
+    pub depth: f64
+}
+
+impl Default for Cell {
+    fn default() -> Cell {
+        Cell {
+            glyph: ' ',
+            color: Color::WHITE,
+            background: None,
+            depth: f64::INFINITY
+        }
+    }
+}
+
+// How framebuffer pixels are turned into terminal cells on present.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PresentMode {
+    // One pixel per cell, drawn with its own glyph.
+    Cells,
+    // Two pixels stacked per cell, drawn as upper/lower half blocks using
+    // the foreground and background colors.
+    HalfBlock,
+    // A 2x4 block of pixels per cell, drawn as a braille pattern.
+    Braille
+}
+
+impl PresentMode {
+    // Pixels per terminal cell, horizontally and vertically.
+    pub fn getCellSize(&self) -> (i32, i32) {
+        match self {
+            PresentMode::Cells => (1, 1),
+            PresentMode::HalfBlock => (1, 2),
+            PresentMode::Braille => (2, 4)
+        }
+    }
+}
+
+// Braille dot bit for each pixel of a 2x4 block, indexed [y][x].
+const BRAILLE_DOTS: [[u32; 2]; 4] = [
+    [0x01, 0x08],
+    [0x02, 0x10],
+    [0x04, 0x20],
+    [0x40, 0x80]
+];
+
+pub struct FrameBuffer {
+    width: i32,
+    height: i32,
+    cells: Vec<Cell>,
+    // Depth of each supersample, `samples` per cell, or empty when nothing
+    // supersampled has been drawn since the last clear.
+    sampleDepths: Vec<f64>,
+    samples: usize,
+    presentMode: PresentMode
+}
+
+impl FrameBuffer {
+    pub fn new(w: i32, h: i32) -> FrameBuffer {
+        let w = w.max(0);
+        let h = h.max(0);
+
+        FrameBuffer {
+            width: w,
+            height: h,
+            cells: vec![Cell::default(); (w * h) as usize],
+            sampleDepths: Vec::new(),
+            samples: 0,
+            presentMode: PresentMode::Cells
+        }
+    }
+
+    // A buffer with enough pixels to fill `cols` x `rows` terminal cells
+    // in the given mode.
+    pub fn forTerminal(cols: i32, rows: i32, mode: PresentMode) -> FrameBuffer {
+        let (sx, sy) = mode.getCellSize();
+
+        let mut fb = FrameBuffer::new(cols * sx, rows * sy);
+        fb.presentMode = mode;
+        fb
+    }
+
+    pub fn getPresentMode(&self) -> PresentMode {
+        self.presentMode
+    }
+
+    pub fn setPresentMode(&mut self, mode: PresentMode) {
+        self.presentMode = mode;
+    }
+
+    pub fn getWidth(&self) -> i32 {
+        self.width
+    }
+
+    pub fn getHeight(&self) -> i32 {
+        self.height
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some((y * self.width + x) as usize)
+    }
+
+    pub fn getCell(&self, x: i32, y: i32) -> Option<&Cell> {
+        self.index(x, y).map(|i| &self.cells[i])
+    }
+
+    // A cell set to a new depth also takes it for all of its sample depths.
+    pub fn setCell(&mut self, x: i32, y: i32, cell: Cell) {
+        if let Some(i) = self.index(x, y) {
+            if self.samples > 0 && cell.depth != self.cells[i].depth {
+                for depth in self.sampleDepths[i * self.samples..(i + 1) * self.samples].iter_mut() {
+                    *depth = cell.depth;
+                }
+            }
+
+            self.cells[i] = cell;
+        }
+    }
+
+    // Depths of the `samples` supersamples of a cell. They start out at the
+    // cell's depth whenever the sample count changes.
+    pub fn getSampleDepths(&mut self, x: i32, y: i32, samples: usize) -> Option<&mut [f64]> {
+        let i = self.index(x, y)?;
+
+        if samples != self.samples {
+            self.samples = samples;
+            self.sampleDepths.clear();
+
+            for cell in self.cells.iter() {
+                let end = self.sampleDepths.len() + samples;
+                self.sampleDepths.resize(end, cell.depth);
+            }
+        }
+
+        Some(&mut self.sampleDepths[i * samples..(i + 1) * samples])
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            *cell = Cell::default();
+        }
+
+        self.clearSampleDepths();
+    }
+
+    // Resets only the depth buffer, keeping whatever glyphs are already drawn.
+    pub fn clearDepth(&mut self) {
+        for cell in self.cells.iter_mut() {
+            cell.depth = f64::INFINITY;
+        }
+
+        self.clearSampleDepths();
+    }
+
+    fn clearSampleDepths(&mut self) {
+        self.sampleDepths.clear();
+        self.samples = 0;
+    }
+
+    // Flushes the whole buffer to the target in one pass and flushes once,
+    // so a terminal never shows a partially drawn frame.
+    pub fn present<T: RenderTarget + ?Sized>(&self, target: &mut T) {
+        let (cols, rows) = target.getSize();
+        let (sx, sy) = self.presentMode.getCellSize();
+
+        let outCols = ((self.width + sx - 1) / sx).min(cols);
+        let outRows = ((self.height + sy - 1) / sy).min(rows);
+
+        for y in 0..outRows {
+            for x in 0..outCols {
+                let cell = match self.presentMode {
+                    PresentMode::Cells => self.cells[(y * self.width + x) as usize],
+                    PresentMode::HalfBlock => self.resolveHalfBlock(x, y),
+                    PresentMode::Braille => self.resolveBraille(x, y)
+                };
+
+                target.putCell(x, y, &cell);
+            }
+        }
+
+        target.flush();
+    }
+
+    fn isCovered(cell: Option<&Cell>) -> bool {
+        match cell {
+            Some(c) => c.glyph != ' ',
+            None => false
+        }
+    }
+
+    fn resolveHalfBlock(&self, x: i32, y: i32) -> Cell {
+        let top = self.getCell(x, y * 2);
+        let bottom = self.getCell(x, y * 2 + 1);
+
+        match (FrameBuffer::isCovered(top), FrameBuffer::isCovered(bottom)) {
+            (true, true) => {
+                let (t, b) = (top.unwrap(), bottom.unwrap());
+
+                if t.color == b.color {
+                    return Cell {
+                        glyph: '█',
+                        background: None,
+                        depth: t.depth.min(b.depth),
+                        ..*t
+                    };
+                }
+
+                Cell {
+                    glyph: '▀',
+                    color: t.color,
+                    background: Some(b.color),
+                    depth: t.depth.min(b.depth)
+                }
+            },
+            (true, false) => Cell {
+                glyph: '▀',
+                ..*top.unwrap()
+            },
+            (false, true) => Cell {
+                glyph: '▄',
+                ..*bottom.unwrap()
+            },
+            (false, false) => Cell::default()
+        }
+    }
+
+    // Lights one dot per covered pixel, colored with the average of the
+    // covered pixels.
+    fn resolveBraille(&self, x: i32, y: i32) -> Cell {
+        let mut bits: u32 = 0;
+        let mut sum: (u32, u32, u32) = (0, 0, 0);
+        let mut count: u32 = 0;
+        let mut depth: f64 = f64::INFINITY;
+
+        for (dy, row) in BRAILLE_DOTS.iter().enumerate() {
+            for (dx, bit) in row.iter().enumerate() {
+                let pixel = self.getCell(x * 2 + dx as i32, y * 4 + dy as i32);
+
+                if FrameBuffer::isCovered(pixel) {
+                    let p = pixel.unwrap();
+
+                    bits |= bit;
+                    sum = (sum.0 + p.color.r as u32, sum.1 + p.color.g as u32, sum.2 + p.color.b as u32);
+                    count += 1;
+                    depth = depth.min(p.depth);
+                }
+            }
+        }
+
+        if count == 0 {
+            return Cell::default();
+        }
+
+        Cell {
+            glyph: std::char::from_u32(0x2800 + bits).unwrap_or(' '),
+            color: Color::new((sum.0 / count) as u8, (sum.1 / count) as u8, (sum.2 / count) as u8),
+            background: None,
+            depth
+        }
+    }
+}
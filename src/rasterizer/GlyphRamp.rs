@@ -0,0 +1,40 @@
+// Characters ordered from darkest to brightest.
+pub const DEFAULT_RAMP: &str = " .:-=+*#%@";
+
+// Maps fragment intensities in [0, 1] onto a ramp of glyphs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphRamp {
+    glyphs: Vec<char>
+}
+
+impl Default for GlyphRamp {
+    fn default() -> GlyphRamp {
+        GlyphRamp::new(DEFAULT_RAMP)
+    }
+}
+
+impl GlyphRamp {
+    // An empty ramp falls back to a single '#'.
+    pub fn new(ramp: &str) -> GlyphRamp {
+        let mut glyphs: Vec<char> = ramp.chars().collect();
+
+        if glyphs.is_empty() {
+            glyphs.push('#');
+        }
+
+        GlyphRamp {
+            glyphs
+        }
+    }
+
+    pub fn getGlyphs(&self) -> &[char] {
+        &self.glyphs
+    }
+
+    pub fn glyphFor(&self, intensity: f64) -> char {
+        let i: f64 = if intensity.is_nan() { 0.0 } else { intensity.clamp(0.0, 1.0) };
+        let index = (i * (self.glyphs.len() - 1) as f64).round() as usize;
+
+        self.glyphs[index]
+    }
+}
@@ -0,0 +1,900 @@
+use crate::vectors::{Vector2::*, Vector3::*};
+use crate::rasterizer::{FrameBuffer::*, GlyphRamp::*, Color::*, EdgeFunction::*, Fragment::*, Shader::*};
+use crate::pipeline::{Pipeline::*, Clipper::*};
+use crate::mesh::Mesh::*;
+use crate::lighting::{Lighting::*, LitShader::*};
+use crate::defs::CELL_ASPECT;
+
+// Comparison applied between an incoming fragment's depth and the depth
+// already stored in the framebuffer. Smaller depths are closer to the viewer.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DepthTest {
+    Less,
+    LessEqual,
+    Always,
+    Never
+}
+
+impl DepthTest {
+    pub fn passes(&self, incoming: f64, stored: f64) -> bool {
+        match self {
+            DepthTest::Less => incoming < stored,
+            DepthTest::LessEqual => incoming <= stored,
+            DepthTest::Always => true,
+            DepthTest::Never => false
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CullMode {
+    None,
+    Front,
+    Back
+}
+
+// Winding order, as seen on screen, that marks a triangle as front facing.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FrontFace {
+    Clockwise,
+    CounterClockwise
+}
+
+impl FrontFace {
+    // Screen space has y pointing down, so a triangle that appears
+    // counter-clockwise to the viewer has a negative signed area.
+    pub fn isFrontFacing(&self, signedArea: f64) -> bool {
+        match self {
+            FrontFace::Clockwise => signedArea > 0.0,
+            FrontFace::CounterClockwise => signedArea < 0.0
+        }
+    }
+}
+
+// Sample points used to estimate how much of each cell a triangle covers.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SampleMode {
+    // One sample per cell, which is either fully covered or not at all.
+    Single,
+    // An N x N grid of samples per cell.
+    Grid(u32),
+    // Four samples on a rotated grid, which resolves near-horizontal and
+    // near-vertical edges better than a 2x2 grid for the same cost.
+    RotatedGrid
+}
+
+impl SampleMode {
+    // Sample offsets from the cell's center, within half a cell.
+    pub fn getOffsets(&self) -> Vec<(f64, f64)> {
+        match self {
+            SampleMode::Single => vec![(0.0, 0.0)],
+            SampleMode::Grid(n) => {
+                let n = (*n).max(1);
+                let step: f64 = 1.0 / n as f64;
+
+                (0..n * n)
+                    .map(|k| {
+                        ((k % n) as f64 * step + step * 0.5 - 0.5, (k / n) as f64 * step + step * 0.5 - 0.5)
+                    })
+                    .collect()
+            },
+            SampleMode::RotatedGrid => vec![
+                (-0.125, -0.375),
+                (0.375, -0.125),
+                (0.125, 0.375),
+                (-0.375, 0.125)
+            ]
+        }
+    }
+}
+
+// How triangles are drawn: filled, as their three edges, or as their
+// three vertices.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PolygonMode {
+    Fill,
+    Line,
+    Point
+}
+
+// Everything but the shaders, kept apart so a draw call can borrow a shader
+// while it writes into the framebuffer.
+struct RasterState {
+    fb: FrameBuffer,
+    depthTest: DepthTest,
+    depthWrite: bool,
+    cullMode: CullMode,
+    frontFace: FrontFace,
+    ramp: GlyphRamp,
+    color: Color,
+    sampleMode: SampleMode,
+    polygonMode: PolygonMode
+}
+
+// Draws triangles into a framebuffer. The shaders only take part in
+// drawTriangle; the rasterize* methods work on screen-space input directly.
+pub struct Rasterizer<V = BasicVertexShader, F = BasicFragmentShader> {
+    state: RasterState,
+    vertexShader: V,
+    fragmentShader: F
+}
+
+impl Rasterizer {
+    pub fn new(width: i32, height: i32) -> Rasterizer {
+        Rasterizer::withFrameBuffer(FrameBuffer::new(width, height))
+    }
+
+    // Rasterizes into an existing buffer, e.g. one from FrameBuffer::forTerminal.
+    pub fn withFrameBuffer(fb: FrameBuffer) -> Rasterizer {
+        Rasterizer::withShaders(fb, BasicVertexShader, BasicFragmentShader)
+    }
+
+    // Twice the signed area of the triangle.
+    pub fn signedArea(v1: &Vector2, v2: &Vector2, v3: &Vector2) -> f64 {
+        (v2.x - v1.x) * (v3.y - v1.y) - (v3.x - v1.x) * (v2.y - v1.y)
+    }
+
+    // Whether the center of cell (ptX, ptY) is covered under the same fill
+    // rule the rasterizer uses. Either winding is accepted, and nothing
+    // outside the guard band counts as covered.
+    pub fn isPointInTriangle(ptX: i32, ptY: i32, v1: &Vector2, v2: &Vector2, v3: &Vector2) -> bool {
+        if ![(ptX as f64, ptY as f64), (v1.x, v1.y), (v2.x, v2.y), (v3.x, v3.y)].iter().all(|(x, y)| inGuardBand(*x, *y)) {
+            return false;
+        }
+
+        let (v2, v3) = if Rasterizer::signedArea(v1, v2, v3) < 0.0 { (v3, v2) } else { (v2, v3) };
+
+        let (x1, y1) = (toFixed(v1.x), toFixed(v1.y));
+        let (x2, y2) = (toFixed(v2.x), toFixed(v2.y));
+        let (x3, y3) = (toFixed(v3.x), toFixed(v3.y));
+
+        let px: i64 = ptX as i64 * SUBPIXEL_ONE + SUBPIXEL_ONE / 2;
+        let py: i64 = ptY as i64 * SUBPIXEL_ONE + SUBPIXEL_ONE / 2;
+
+        let edges: [EdgeFunction; 3] = [
+            EdgeFunction::new(x2, y2, x3, y3),
+            EdgeFunction::new(x3, y3, x1, y1),
+            EdgeFunction::new(x1, y1, x2, y2)
+        ];
+
+        edges[2].evaluate(x3, y3) > 0 && edges.iter().all(|e| e.evaluate(px, py) + e.getBias() >= 0)
+    }
+
+    // Glyph that best follows a line with the given on-screen direction, for
+    // cells `cellAspect` times as tall as they are wide. Screen y points down,
+    // so a line rising to the right is '/'.
+    pub fn slopeGlyph(dx: f64, dy: f64, cellAspect: f64) -> char {
+        let mut angle: f64 = (dy * cellAspect).atan2(dx).to_degrees();
+
+        if angle < 0.0 {
+            angle += 180.0;
+        }
+
+        if !(22.5..157.5).contains(&angle) {
+            '-'
+        } else if angle < 67.5 {
+            '\\'
+        } else if angle < 112.5 {
+            '|'
+        } else {
+            '/'
+        }
+    }
+}
+
+impl<V, F> Rasterizer<V, F> {
+    pub fn withShaders(fb: FrameBuffer, vertexShader: V, fragmentShader: F) -> Rasterizer<V, F> {
+        Rasterizer {
+            state: RasterState {
+                fb,
+                depthTest: DepthTest::Less,
+                depthWrite: true,
+                cullMode: CullMode::None,
+                frontFace: FrontFace::CounterClockwise,
+                ramp: Default::default(),
+                color: Color::WHITE,
+                sampleMode: SampleMode::Single,
+                polygonMode: PolygonMode::Fill
+            },
+            vertexShader,
+            fragmentShader
+        }
+    }
+
+    pub fn getFrameBuffer(&self) -> &FrameBuffer {
+        &self.state.fb
+    }
+
+    pub fn getFrameBufferMut(&mut self) -> &mut FrameBuffer {
+        &mut self.state.fb
+    }
+
+    pub fn getDepthTest(&self) -> DepthTest {
+        self.state.depthTest
+    }
+
+    pub fn setDepthTest(&mut self, depthTest: DepthTest) {
+        self.state.depthTest = depthTest;
+    }
+
+    pub fn getDepthWrite(&self) -> bool {
+        self.state.depthWrite
+    }
+
+    // When disabled, fragments that pass the depth test still draw but leave
+    // the stored depth untouched.
+    pub fn setDepthWrite(&mut self, enabled: bool) {
+        self.state.depthWrite = enabled;
+    }
+
+    pub fn getCullMode(&self) -> CullMode {
+        self.state.cullMode
+    }
+
+    pub fn setCullMode(&mut self, cullMode: CullMode) {
+        self.state.cullMode = cullMode;
+    }
+
+    pub fn getFrontFace(&self) -> FrontFace {
+        self.state.frontFace
+    }
+
+    pub fn setFrontFace(&mut self, frontFace: FrontFace) {
+        self.state.frontFace = frontFace;
+    }
+
+    pub fn isCulled(&self, signedArea: f64) -> bool {
+        self.state.isCulled(signedArea)
+    }
+
+    pub fn getRamp(&self) -> &GlyphRamp {
+        &self.state.ramp
+    }
+
+    pub fn setRamp(&mut self, ramp: GlyphRamp) {
+        self.state.ramp = ramp;
+    }
+
+    pub fn getColor(&self) -> Color {
+        self.state.color
+    }
+
+    // Base color of subsequently drawn triangles. Each fragment's color is
+    // this scaled by its intensity.
+    pub fn setColor(&mut self, color: Color) {
+        self.state.color = color;
+    }
+
+    pub fn getSampleMode(&self) -> SampleMode {
+        self.state.sampleMode
+    }
+
+    // With more than one sample, partially covered cells are drawn with a
+    // dimmer glyph over empty cells, or blend their color into the geometry
+    // already there.
+    pub fn setSampleMode(&mut self, sampleMode: SampleMode) {
+        self.state.sampleMode = sampleMode;
+    }
+
+    pub fn getPolygonMode(&self) -> PolygonMode {
+        self.state.polygonMode
+    }
+
+    // Applies to every triangle entry point. Edges are drawn as lines with
+    // slope glyphs unless the fragment callback picks a glyph itself.
+    pub fn setPolygonMode(&mut self, polygonMode: PolygonMode) {
+        self.state.polygonMode = polygonMode;
+    }
+
+    pub fn getVertexShader(&self) -> &V {
+        &self.vertexShader
+    }
+
+    pub fn getVertexShaderMut(&mut self) -> &mut V {
+        &mut self.vertexShader
+    }
+
+    pub fn setVertexShader(&mut self, vertexShader: V) {
+        self.vertexShader = vertexShader;
+    }
+
+    pub fn getFragmentShader(&self) -> &F {
+        &self.fragmentShader
+    }
+
+    pub fn getFragmentShaderMut(&mut self) -> &mut F {
+        &mut self.fragmentShader
+    }
+
+    pub fn setFragmentShader(&mut self, fragmentShader: F) {
+        self.fragmentShader = fragmentShader;
+    }
+
+
+    // Vertices are in screen space: x/y are cell coordinates and z is depth.
+    // Draws at full intensity, the brightest glyph of the ramp.
+    pub fn rasterizeTriangle(&mut self, v1: &Vector3, v2: &Vector3, v3: &Vector3) {
+        self.rasterizeShadedTriangle(v1, v2, v3, [1.0, 1.0, 1.0]);
+    }
+
+    // Like rasterizeTriangle, with a per-vertex intensity in [0, 1] that is
+    // interpolated across the triangle and mapped through the glyph ramp.
+    pub fn rasterizeShadedTriangle(&mut self, v1: &Vector3, v2: &Vector3, v3: &Vector3, intensities: [f64; 3]) {
+        let color = self.state.color;
+
+        self.rasterizeVaryingTriangle(
+            &ScreenVertex::fromScreen(v1, vec![intensities[0]]),
+            &ScreenVertex::fromScreen(v2, vec![intensities[1]]),
+            &ScreenVertex::fromScreen(v3, vec![intensities[2]]),
+            |fragment| {
+                let intensity = fragment.varyings[0];
+                Some(Shade::new(color.scaled(intensity), intensity))
+            });
+    }
+
+    // Rasterizes a triangle whose vertices carry any number of varyings,
+    // interpolated with 1/w correction so they stay fixed to the surface
+    // under perspective. `shade` runs once per covered cell that passes the
+    // depth test and returns what to draw, or None to discard the fragment.
+    // Cells are sampled at their centers and edges follow the top-left fill
+    // rule, so triangles sharing an edge never both cover a sample.
+    pub fn rasterizeVaryingTriangle<S>(&mut self, v1: &ScreenVertex, v2: &ScreenVertex, v3: &ScreenVertex, shade: S)
+        where S: FnMut(&Fragment) -> Option<Shade> {
+        self.state.rasterize(v1, v2, v3, shade);
+    }
+
+    // Draws a screen-space line in the current color, using whichever of
+    // '-', '|', '/' and '\\' best follows its slope.
+    pub fn rasterizeLine(&mut self, v1: &Vector3, v2: &Vector3) {
+        let color = self.state.color;
+
+        self.rasterizeVaryingLine(&ScreenVertex::fromScreen(v1, Vec::new()), &ScreenVertex::fromScreen(v2, Vec::new()),
+            |_| Some(Shade::new(color, 1.0)));
+    }
+
+    // Walks the cells between the endpoints with Bresenham's algorithm after
+    // clipping the line to the framebuffer. Depth and varyings are
+    // interpolated between the endpoints like a triangle's.
+    pub fn rasterizeVaryingLine<S>(&mut self, v1: &ScreenVertex, v2: &ScreenVertex, mut shade: S)
+        where S: FnMut(&Fragment) -> Option<Shade> {
+        self.state.line(v1, v2, &mut shade);
+    }
+
+    // Draws a single screen-space point in the current color.
+    pub fn rasterizePoint(&mut self, v: &Vector3) {
+        let color = self.state.color;
+
+        self.rasterizeVaryingPoint(&ScreenVertex::fromScreen(v, Vec::new()), |_| Some(Shade::new(color, 1.0)));
+    }
+
+    pub fn rasterizeVaryingPoint<S>(&mut self, v: &ScreenVertex, mut shade: S)
+        where S: FnMut(&Fragment) -> Option<Shade> {
+        self.state.point(v, &mut shade);
+    }
+
+    // Runs a mesh through the pipeline's transforms onto its viewport, in the
+    // current color tinted by the vertex colors. Works whatever shaders the
+    // rasterizer holds; use drawIndexed to draw a mesh with those instead.
+    pub fn drawMesh(&mut self, mesh: &Mesh, pipeline: &Pipeline) {
+        let uniforms = BasicUniforms::new(pipeline.getModelViewProjection(), self.state.color);
+
+        self.state.drawIndexed(&VertexColorShader, &VertexColorShader, mesh.getVertices(), mesh.getIndices(), &uniforms, pipeline.getViewport());
+    }
+
+    // Like drawMesh, but lit. The lighting's shading model picks where it is
+    // evaluated, and the light received sets both the glyph and the color.
+    // Flat shading uses face normals, so it also suits meshes without normals.
+    pub fn drawLitMesh(&mut self, mesh: &Mesh, pipeline: &Pipeline, lighting: &Lighting) {
+        let uniforms = LitUniforms::new(pipeline, self.state.color, lighting);
+        let viewport = pipeline.getViewport();
+
+        if lighting.getShadingModel() != ShadingModel::Flat {
+            self.state.drawIndexed(&LitShader, &LitShader, mesh.getVertices(), mesh.getIndices(), &uniforms, viewport);
+            return;
+        }
+
+        self.state.drawIndexedWith(&LitShader, mesh.getVertices(), mesh.getIndices(), &uniforms,
+            |state, [c1, c2, c3]| {
+                let shade = LitShader::shadeFace([c1, c2, c3], &uniforms);
+                state.drawClipTriangle(c1, c2, c3, viewport, &mut |_: &Fragment| Some(shade));
+            });
+    }
+}
+
+impl<V, F> Rasterizer<V, F> where V: VertexShader, F: FragmentShader<Uniforms = V::Uniforms> {
+    // Runs the vertex shader on each input, clips the triangle in clip space
+    // and rasterizes what is left over the whole framebuffer, shading every
+    // fragment with the fragment shader.
+    pub fn drawTriangle(&mut self, inputs: [&V::Input; 3], uniforms: &V::Uniforms) {
+        let c1 = self.vertexShader.shade(inputs[0], uniforms);
+        let c2 = self.vertexShader.shade(inputs[1], uniforms);
+        let c3 = self.vertexShader.shade(inputs[2], uniforms);
+
+        let viewport = Viewport::fromFrameBuffer(&self.state.fb);
+        let fragmentShader = &self.fragmentShader;
+
+        self.state.drawClipTriangle(&c1, &c2, &c3, &viewport,
+            &mut |fragment: &Fragment| fragmentShader.shade(fragment, uniforms));
+    }
+
+    // Draws an indexed triangle list, running the vertex shader once per
+    // referenced vertex however many triangles share it.
+    pub fn drawIndexed(&mut self, vertices: &[V::Input], indices: &[u32], uniforms: &V::Uniforms) {
+        let viewport = Viewport::fromFrameBuffer(&self.state.fb);
+
+        self.state.drawIndexed(&self.vertexShader, &self.fragmentShader, vertices, indices, uniforms, &viewport);
+    }
+
+    pub fn drawLine(&mut self, inputs: [&V::Input; 2], uniforms: &V::Uniforms) {
+        let c1 = self.vertexShader.shade(inputs[0], uniforms);
+        let c2 = self.vertexShader.shade(inputs[1], uniforms);
+
+        let viewport = Viewport::fromFrameBuffer(&self.state.fb);
+        let fragmentShader = &self.fragmentShader;
+
+        if let Some([a, b]) = Clipper::clipLine(&c1, &c2) {
+            self.state.line(&viewport.toScreenVertex(&a), &viewport.toScreenVertex(&b),
+                &mut |fragment: &Fragment| fragmentShader.shade(fragment, uniforms));
+        }
+    }
+
+    pub fn drawPoint(&mut self, input: &V::Input, uniforms: &V::Uniforms) {
+        let clip = self.vertexShader.shade(input, uniforms);
+
+        if !Clipper::isInside(&clip.position) {
+            return;
+        }
+
+        let viewport = Viewport::fromFrameBuffer(&self.state.fb);
+        let fragmentShader = &self.fragmentShader;
+
+        self.state.point(&viewport.toScreenVertex(&clip), &mut |fragment: &Fragment| fragmentShader.shade(fragment, uniforms));
+    }
+}
+
+impl RasterState {
+    fn drawIndexed<VS, FS>(&mut self, vertexShader: &VS, fragmentShader: &FS, vertices: &[VS::Input], indices: &[u32],
+        uniforms: &VS::Uniforms, viewport: &Viewport)
+        where VS: VertexShader, FS: FragmentShader<Uniforms = VS::Uniforms> {
+        let mut shade = |fragment: &Fragment| fragmentShader.shade(fragment, uniforms);
+
+        self.drawIndexedWith(vertexShader, vertices, indices, uniforms, |state, [c1, c2, c3]| {
+            state.drawClipTriangle(c1, c2, c3, viewport, &mut shade);
+        });
+    }
+
+    // Runs the vertex shader on the vertices of each triangle, then hands the
+    // shaded corners to `draw`.
+    fn drawIndexedWith<VS, D>(&mut self, vertexShader: &VS, vertices: &[VS::Input], indices: &[u32], uniforms: &VS::Uniforms, mut draw: D)
+        where VS: VertexShader, D: FnMut(&mut RasterState, [&ClipVertex; 3]) {
+        // Post-transform cache, filled the first time an index is seen.
+        let mut transformed: Vec<Option<ClipVertex>> = vec![None; vertices.len()];
+
+        for triangle in indices.chunks_exact(3) {
+            if triangle.iter().any(|&i| i as usize >= vertices.len()) {
+                continue;
+            }
+
+            for &i in triangle.iter() {
+                if transformed[i as usize].is_none() {
+                    transformed[i as usize] = Some(vertexShader.shade(&vertices[i as usize], uniforms));
+                }
+            }
+
+            if let [Some(c1), Some(c2), Some(c3)] = [&transformed[triangle[0] as usize], &transformed[triangle[1] as usize], &transformed[triangle[2] as usize]] {
+                draw(self, [c1, c2, c3]);
+            }
+        }
+    }
+
+    // Clips a clip-space triangle and rasterizes what remains onto the
+    // viewport, honouring the polygon mode.
+    fn drawClipTriangle<S>(&mut self, c1: &ClipVertex, c2: &ClipVertex, c3: &ClipVertex, viewport: &Viewport, shade: &mut S)
+        where S: FnMut(&Fragment) -> Option<Shade> {
+        if self.polygonMode != PolygonMode::Fill {
+            // Clipping would add edges along the clip planes, so clip the
+            // original edges as lines and only use the polygon for culling.
+            let polygon = Clipper::clipPolygon(&[c1.clone(), c2.clone(), c3.clone()]);
+
+            if polygon.len() < 3 {
+                return;
+            }
+
+            let (a, b, c) = (viewport.toScreenSpace(&polygon[0].position), viewport.toScreenSpace(&polygon[1].position), viewport.toScreenSpace(&polygon[2].position));
+            let area = Rasterizer::signedArea(&Vector2::new(a.x, a.y), &Vector2::new(b.x, b.y), &Vector2::new(c.x, c.y));
+
+            if area == 0.0 || !area.is_finite() || self.isCulled(area) {
+                return;
+            }
+
+            for (from, to) in [(c1, c2), (c2, c3), (c3, c1)].iter() {
+                if self.polygonMode == PolygonMode::Point {
+                    if Clipper::isInside(&from.position) {
+                        self.point(&viewport.toScreenVertex(from), shade);
+                    }
+                } else if let Some([a, b]) = Clipper::clipLine(from, to) {
+                    self.line(&viewport.toScreenVertex(&a), &viewport.toScreenVertex(&b), shade);
+                }
+            }
+
+            return;
+        }
+
+        for [a, b, c] in Clipper::clipTriangle(c1, c2, c3).iter() {
+            self.rasterize(&viewport.toScreenVertex(a), &viewport.toScreenVertex(b), &viewport.toScreenVertex(c), &mut *shade);
+        }
+    }
+
+    fn isCulled(&self, signedArea: f64) -> bool {
+        match self.cullMode {
+            CullMode::None => false,
+            CullMode::Front => self.frontFace.isFrontFacing(signedArea),
+            CullMode::Back => !self.frontFace.isFrontFacing(signedArea)
+        }
+    }
+
+    fn rasterize<S>(&mut self, v1: &ScreenVertex, v2: &ScreenVertex, v3: &ScreenVertex, mut shade: S)
+        where S: FnMut(&Fragment) -> Option<Shade> {
+        let (p1, p2, p3) = (&v1.position, &v2.position, &v3.position);
+        let area: f64 = Rasterizer::signedArea(&Vector2::new(p1.x, p1.y), &Vector2::new(p2.x, p2.y), &Vector2::new(p3.x, p3.y));
+
+        if area == 0.0 || !area.is_finite() || self.isCulled(area) {
+            return;
+        }
+
+        // Clipped geometry always lies inside; only triangles handed straight
+        // to rasterizeTriangle can reach this far out.
+        if ![p1, p2, p3].iter().all(|p| inGuardBand(p.x, p.y)) {
+            return;
+        }
+
+        match self.polygonMode {
+            PolygonMode::Fill => {},
+            PolygonMode::Line => {
+                self.line(v1, v2, &mut shade);
+                self.line(v2, v3, &mut shade);
+                self.line(v3, v1, &mut shade);
+                return;
+            },
+            PolygonMode::Point => {
+                self.point(v1, &mut shade);
+                self.point(v2, &mut shade);
+                self.point(v3, &mut shade);
+                return;
+            }
+        }
+
+        // Edge setup wants positive area, so flip the winding if needed.
+        let (v2, v3) = if area < 0.0 { (v3, v2) } else { (v2, v3) };
+        let vertices: [&ScreenVertex; 3] = [v1, v2, v3];
+
+        let (x1, y1) = (toFixed(v1.position.x), toFixed(v1.position.y));
+        let (x2, y2) = (toFixed(v2.position.x), toFixed(v2.position.y));
+        let (x3, y3) = (toFixed(v3.position.x), toFixed(v3.position.y));
+
+        // Each edge is opposite the vertex whose weight it yields.
+        let edges: [EdgeFunction; 3] = [
+            EdgeFunction::new(x2, y2, x3, y3),
+            EdgeFunction::new(x3, y3, x1, y1),
+            EdgeFunction::new(x1, y1, x2, y2)
+        ];
+
+        let fixedArea: i64 = edges[2].evaluate(x3, y3);
+
+        if fixedArea <= 0 {
+            return;
+        }
+
+        let invArea: f64 = 1.0 / fixedArea as f64;
+        let count: usize = v1.varyings.len().min(v2.varyings.len()).min(v3.varyings.len());
+        let biases: [i64; 3] = [edges[0].getBias(), edges[1].getBias(), edges[2].getBias()];
+        let stepX: [i64; 3] = [edges[0].stepX(), edges[1].stepX(), edges[2].stepX()];
+        let stepY: [i64; 3] = [edges[0].stepY(), edges[1].stepY(), edges[2].stepY()];
+
+        // Per sample, the offset of each edge's value from the cell center.
+        let sampleOffsets: Vec<[i64; 3]> = self.sampleMode.getOffsets().iter()
+            .map(|(ox, oy)| {
+                let (fx, fy) = (toFixed(*ox), toFixed(*oy));
+                [edges[0].offset(fx, fy), edges[1].offset(fx, fy), edges[2].offset(fx, fy)]
+            })
+            .collect();
+
+        // Samples reach up to half a cell either side of a cell's center.
+        let minX: i32 = 0.max(p1.x.min(p2.x.min(p3.x)).floor() as i32 - 1);
+        let minY: i32 = 0.max(p1.y.min(p2.y.min(p3.y)).floor() as i32 - 1);
+
+        let maxX: i32 = self.fb.getWidth().min(p1.x.max(p2.x.max(p3.x)).floor() as i32 + 1);
+        let maxY: i32 = self.fb.getHeight().min(p1.y.max(p2.y.max(p3.y)).floor() as i32 + 1);
+
+        if minX >= maxX || minY >= maxY {
+            return;
+        }
+
+        let startX: i64 = minX as i64 * SUBPIXEL_ONE + SUBPIXEL_ONE / 2;
+        let startY: i64 = minY as i64 * SUBPIXEL_ONE + SUBPIXEL_ONE / 2;
+
+        let mut row: [i64; 3] = [
+            edges[0].evaluate(startX, startY),
+            edges[1].evaluate(startX, startY),
+            edges[2].evaluate(startX, startY)
+        ];
+
+        let mut fragment = Fragment {
+            x: 0,
+            y: 0,
+            depth: 0.0,
+            coverage: 0.0,
+            varyings: vec![0.0; count]
+        };
+
+        // With several samples per cell each keeps its own depth, so an edge
+        // cell holds both the near triangle's samples and whatever lies
+        // behind the rest. `depths` is the current cell's, updated in place.
+        let samples = sampleOffsets.len();
+        let mut depths: Vec<f64> = vec![0.0; samples];
+
+        for j in minY..maxY {
+            let mut values: [i64; 3] = row;
+
+            for i in minX..maxX {
+                let center: [i64; 3] = values;
+
+                for k in 0..3 {
+                    values[k] += stepX[k];
+                }
+
+                let stored = match self.fb.getCell(i, j) {
+                    Some(cell) => *cell,
+                    None => continue
+                };
+
+                if samples > 1 {
+                    if let Some(stored) = self.fb.getSampleDepths(i, j, samples) {
+                        depths.copy_from_slice(stored);
+                    }
+                } else {
+                    depths[0] = stored.depth;
+                }
+
+                let mut covered: usize = 0;
+                let mut depthSum: f64 = 0.0;
+
+                for value in fragment.varyings.iter_mut() {
+                    *value = 0.0;
+                }
+
+                for (k, offsets) in sampleOffsets.iter().enumerate() {
+                    let e: [i64; 3] = [center[0] + offsets[0], center[1] + offsets[1], center[2] + offsets[2]];
+
+                    if e[0] + biases[0] < 0 || e[1] + biases[1] < 0 || e[2] + biases[2] < 0 {
+                        continue;
+                    }
+
+                    let weights: [f64; 3] = [e[0] as f64 * invArea, e[1] as f64 * invArea, e[2] as f64 * invArea];
+                    let sampleDepth: f64 = weights[0] * vertices[0].position.z + weights[1] * vertices[1].position.z + weights[2] * vertices[2].position.z;
+
+                    if !self.depthTest.passes(sampleDepth, depths[k]) {
+                        continue;
+                    }
+
+                    covered += 1;
+                    depthSum += sampleDepth;
+
+                    if self.depthWrite {
+                        depths[k] = sampleDepth;
+                    }
+
+                    // Varyings divided by w are linear in screen space, as
+                    // is 1/w itself, so interpolate both and divide back.
+                    let perspective: [f64; 3] = [weights[0] * vertices[0].invW, weights[1] * vertices[1].invW, weights[2] * vertices[2].invW];
+                    let invW: f64 = perspective[0] + perspective[1] + perspective[2];
+
+                    for (n, value) in fragment.varyings.iter_mut().enumerate() {
+                        *value += (perspective[0] * vertices[0].varyings[n]
+                            + perspective[1] * vertices[1].varyings[n]
+                            + perspective[2] * vertices[2].varyings[n]) / invW;
+                    }
+                }
+
+                if covered == 0 {
+                    continue;
+                }
+
+                let depth = depthSum / covered as f64;
+
+                for value in fragment.varyings.iter_mut() {
+                    *value /= covered as f64;
+                }
+
+                fragment.x = i;
+                fragment.y = j;
+                fragment.depth = depth;
+                fragment.coverage = covered as f64 / samples as f64;
+
+                let output = match shade(&fragment) {
+                    Some(output) => output,
+                    None => continue
+                };
+
+                let coverage = fragment.coverage;
+                let (glyph, color) = if coverage >= 1.0 {
+                    (output.glyph.unwrap_or_else(|| self.ramp.glyphFor(output.intensity)), output.color)
+                } else if stored.glyph == ' ' {
+                    (output.glyph.unwrap_or_else(|| self.ramp.glyphFor(output.intensity * coverage)), output.color.scaled(coverage))
+                } else {
+                    let glyph = if coverage >= 0.5 {
+                        output.glyph.unwrap_or_else(|| self.ramp.glyphFor(output.intensity))
+                    } else {
+                        stored.glyph
+                    };
+
+                    (glyph, Color::lerp(&stored.color, &output.color, coverage))
+                };
+
+                // The cell's own depth is its nearest sample's. Setting it
+                // resets the sample depths, so they are written back after.
+                self.fb.setCell(i, j, Cell {
+                    glyph,
+                    color,
+                    depth: if self.depthWrite { depths.iter().cloned().fold(f64::INFINITY, f64::min) } else { stored.depth },
+                    ..stored
+                });
+
+                if self.depthWrite && samples > 1 {
+                    if let Some(stored) = self.fb.getSampleDepths(i, j, samples) {
+                        stored.copy_from_slice(&depths);
+                    }
+                }
+            }
+
+            for k in 0..3 {
+                row[k] += stepY[k];
+            }
+        }
+    }
+
+    fn line<S>(&mut self, v1: &ScreenVertex, v2: &ScreenVertex, shade: &mut S)
+        where S: FnMut(&Fragment) -> Option<Shade> {
+        let (p1, p2) = (&v1.position, &v2.position);
+        let (dx, dy) = (p2.x - p1.x, p2.y - p1.y);
+
+        if !dx.is_finite() || !dy.is_finite() {
+            return;
+        }
+
+        let (width, height) = (self.fb.getWidth(), self.fb.getHeight());
+
+        if width <= 0 || height <= 0 {
+            return;
+        }
+
+        // Liang-Barsky against the framebuffer, as a range of the original
+        // line's parameter so interpolation stays relative to the endpoints.
+        // Cells cover [0, width) x [0, height), so the right and bottom edges
+        // are open: whatever lies only on them is outside.
+        let mut tStart: f64 = 0.0;
+        let mut tEnd: f64 = 1.0;
+
+        for (p, q, open) in [(-dx, p1.x, false), (dx, width as f64 - p1.x, true), (-dy, p1.y, false), (dy, height as f64 - p1.y, true)].iter() {
+            if *p == 0.0 {
+                if *q < 0.0 || (*open && *q == 0.0) {
+                    return;
+                }
+            } else if *p < 0.0 {
+                tStart = tStart.max(q / p);
+            } else {
+                tEnd = tEnd.min(q / p);
+            }
+        }
+
+        if tStart > tEnd {
+            return;
+        }
+
+        // A line that only touches the box at a corner or edge point.
+        if tStart == tEnd && (p1.x + dx * tStart >= width as f64 || p1.y + dy * tStart >= height as f64) {
+            return;
+        }
+
+        let (sx, sy) = self.fb.getPresentMode().getCellSize();
+        let glyph = Rasterizer::slopeGlyph(dx, dy, CELL_ASPECT * sx as f64 / sy as f64);
+
+        let toCell = |t: f64| -> (i32, i32) {
+            (((p1.x + dx * t).floor() as i32).max(0).min(width - 1), ((p1.y + dy * t).floor() as i32).max(0).min(height - 1))
+        };
+
+        let (mut x, mut y) = toCell(tStart);
+        let (endX, endY) = toCell(tEnd);
+
+        let stepX: i32 = if endX > x { 1 } else { -1 };
+        let stepY: i32 = if endY > y { 1 } else { -1 };
+        let spanX: i32 = (endX - x).abs();
+        let spanY: i32 = (endY - y).abs();
+
+        let lengthSq: f64 = dx * dx + dy * dy;
+        let count: usize = v1.varyings.len().min(v2.varyings.len());
+        let mut error: i32 = spanX - spanY;
+
+        loop {
+            // Parameter of the cell center projected onto the line.
+            let t: f64 = if lengthSq == 0.0 {
+                0.0
+            } else {
+                (((x as f64 + 0.5 - p1.x) * dx + (y as f64 + 0.5 - p1.y) * dy) / lengthSq).clamp(0.0, 1.0)
+            };
+
+            let (w1, w2) = ((1.0 - t) * v1.invW, t * v2.invW);
+
+            let fragment = Fragment {
+                x,
+                y,
+                depth: p1.z + (p2.z - p1.z) * t,
+                coverage: 1.0,
+                varyings: (0..count).map(|n| (w1 * v1.varyings[n] + w2 * v2.varyings[n]) / (w1 + w2)).collect()
+            };
+
+            self.plot(&fragment, Some(glyph), shade);
+
+            if x == endX && y == endY {
+                break;
+            }
+
+            let doubled = error * 2;
+
+            if doubled > -spanY {
+                error -= spanY;
+                x += stepX;
+            }
+
+            if doubled < spanX {
+                error += spanX;
+                y += stepY;
+            }
+        }
+    }
+
+    fn point<S>(&mut self, v: &ScreenVertex, shade: &mut S)
+        where S: FnMut(&Fragment) -> Option<Shade> {
+        let p = &v.position;
+
+        if !p.x.is_finite() || !p.y.is_finite() {
+            return;
+        }
+
+        let fragment = Fragment {
+            x: p.x.floor() as i32,
+            y: p.y.floor() as i32,
+            depth: p.z,
+            coverage: 1.0,
+            varyings: v.varyings.clone()
+        };
+
+        self.plot(&fragment, None, shade);
+    }
+
+    // Depth tests and shades a single line or point fragment. Without a glyph
+    // from either the caller or the callback, the ramp picks one.
+    fn plot<S>(&mut self, fragment: &Fragment, glyph: Option<char>, shade: &mut S)
+        where S: FnMut(&Fragment) -> Option<Shade> {
+        let stored = match self.fb.getCell(fragment.x, fragment.y) {
+            Some(cell) => *cell,
+            None => return
+        };
+
+        if !self.depthTest.passes(fragment.depth, stored.depth) {
+            return;
+        }
+
+        let output = match shade(fragment) {
+            Some(output) => output,
+            None => return
+        };
+
+        self.fb.setCell(fragment.x, fragment.y, Cell {
+            glyph: output.glyph.or(glyph).unwrap_or_else(|| self.ramp.glyphFor(output.intensity)),
+            color: output.color,
+            depth: if self.depthWrite { fragment.depth } else { stored.depth },
+            ..stored
+        });
+    }
+}
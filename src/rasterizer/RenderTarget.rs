@@ -0,0 +1,212 @@
+use crate::rasterizer::{FrameBuffer::*, Color::*};
+use pancurses::{Window, COLOR_PAIR};
+use std::collections::HashMap;
+use std::io::Write;
+
+// Anything a FrameBuffer can be presented to.
+pub trait RenderTarget {
+    // Width and height in cells.
+    fn getSize(&self) -> (i32, i32);
+
+    fn putCell(&mut self, x: i32, y: i32, cell: &Cell);
+
+    // Called once after every cell of a frame has been written.
+    fn flush(&mut self);
+}
+
+// Draws through curses, quantizing colors to 8 or 256 color pairs. Curses
+// cannot address 24-bit color, so TrueColor is treated as Xterm256.
+pub struct CursesTarget<'a> {
+    window: &'a Window,
+    colorMode: ColorMode,
+    // Foreground and background palette indices to the color pair
+    // allocated for them. -1 is the terminal's default color.
+    pairs: HashMap<(i16, i16), i16>
+}
+
+impl CursesTarget<'_> {
+    // Detects the color capability of the initialized curses screen.
+    pub fn new(window: &Window) -> CursesTarget<'_> {
+        let mode = if pancurses::has_colors() {
+            pancurses::start_color();
+
+            if pancurses::COLORS() >= 256 && pancurses::COLOR_PAIRS() > 256 {
+                ColorMode::Xterm256
+            } else {
+                ColorMode::Ansi8
+            }
+        } else {
+            ColorMode::Monochrome
+        };
+
+        CursesTarget::withColorMode(window, mode)
+    }
+
+    pub fn withColorMode(window: &Window, colorMode: ColorMode) -> CursesTarget<'_> {
+        if colorMode != ColorMode::Monochrome {
+            pancurses::start_color();
+            pancurses::use_default_colors();
+        }
+
+        CursesTarget {
+            window,
+            colorMode: colorMode.min(ColorMode::Xterm256),
+            pairs: HashMap::new()
+        }
+    }
+
+    pub fn getColorMode(&self) -> ColorMode {
+        self.colorMode
+    }
+
+    fn paletteIndex(&self, color: &Color) -> i16 {
+        match self.colorMode {
+            ColorMode::Monochrome => -1,
+            ColorMode::Ansi8 => color.toAnsi8() as i16,
+            _ => color.toXterm256() as i16
+        }
+    }
+
+    // Color pair for the given colors, allocating one on first use. Falls
+    // back to the default pair once curses runs out.
+    fn pairFor(&mut self, foreground: &Color, background: Option<&Color>) -> i16 {
+        if self.colorMode == ColorMode::Monochrome {
+            return 0;
+        }
+
+        let key = (self.paletteIndex(foreground), background.map_or(-1, |c| self.paletteIndex(c)));
+
+        if let Some(pair) = self.pairs.get(&key) {
+            return *pair;
+        }
+
+        // Pair numbers must be below COLOR_PAIRS and fit the i16 curses takes.
+        let limit = (pancurses::COLOR_PAIRS().max(0) as usize).min(i16::MAX as usize + 1);
+
+        if self.pairs.len() + 1 >= limit {
+            return 0;
+        }
+
+        let pair = (self.pairs.len() + 1) as i16;
+
+        pancurses::init_pair(pair, key.0, key.1);
+        self.pairs.insert(key, pair);
+        pair
+    }
+}
+
+impl RenderTarget for CursesTarget<'_> {
+    fn getSize(&self) -> (i32, i32) {
+        (self.window.get_max_x(), self.window.get_max_y())
+    }
+
+    fn putCell(&mut self, x: i32, y: i32, cell: &Cell) {
+        let pair = self.pairFor(&cell.color, cell.background.as_ref());
+
+        self.window.attrset(COLOR_PAIR(pair as u32));
+
+        if cell.glyph.is_ascii() {
+            self.window.mvaddch(y, x, cell.glyph);
+        } else {
+            self.window.mvaddstr(y, x, cell.glyph.to_string());
+        }
+    }
+
+    fn flush(&mut self) {
+        self.window.attrset(COLOR_PAIR(0));
+        self.window.refresh();
+    }
+}
+
+// Writes frames as ANSI escape sequences, which unlike curses can carry
+// 24-bit color. Each flush redraws the whole frame from the top-left corner.
+pub struct AnsiTarget<W: Write> {
+    writer: W,
+    width: i32,
+    height: i32,
+    colorMode: ColorMode,
+    cells: Vec<Cell>
+}
+
+impl<W: Write> AnsiTarget<W> {
+    // Detects the color capability from the environment.
+    pub fn new(writer: W, width: i32, height: i32) -> AnsiTarget<W> {
+        AnsiTarget::withColorMode(writer, width, height, ColorMode::detect())
+    }
+
+    pub fn withColorMode(writer: W, width: i32, height: i32, colorMode: ColorMode) -> AnsiTarget<W> {
+        let width = width.max(0);
+        let height = height.max(0);
+
+        AnsiTarget {
+            writer,
+            width,
+            height,
+            colorMode,
+            cells: vec![Cell::default(); (width * height) as usize]
+        }
+    }
+
+    pub fn getColorMode(&self) -> ColorMode {
+        self.colorMode
+    }
+
+    pub fn getWriter(&self) -> &W {
+        &self.writer
+    }
+
+    // Encodes the current frame without writing it.
+    pub fn encodeFrame(&self) -> String {
+        let mut out = String::new();
+        let mut current: Option<Color> = None;
+        let mut currentBackground: Option<Option<Color>> = None;
+
+        for y in 0..self.height {
+            out.push_str(&format!("\x1b[{};1H", y + 1));
+
+            for x in 0..self.width {
+                let cell = &self.cells[(y * self.width + x) as usize];
+
+                if current != Some(cell.color) {
+                    out.push_str(&self.colorMode.foregroundEscape(&cell.color));
+                    current = Some(cell.color);
+                }
+
+                if currentBackground != Some(cell.background) {
+                    out.push_str(&self.colorMode.backgroundEscape(cell.background.as_ref()));
+                    currentBackground = Some(cell.background);
+                }
+
+                out.push(cell.glyph);
+            }
+        }
+
+        if self.colorMode != ColorMode::Monochrome {
+            out.push_str("\x1b[0m");
+        }
+
+        out
+    }
+}
+
+impl<W: Write> RenderTarget for AnsiTarget<W> {
+    fn getSize(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    fn putCell(&mut self, x: i32, y: i32, cell: &Cell) {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return;
+        }
+
+        self.cells[(y * self.width + x) as usize] = *cell;
+    }
+
+    fn flush(&mut self) {
+        let frame = self.encodeFrame();
+
+        // A closed terminal is not worth aborting the render loop over.
+        let _ = self.writer.write_all(frame.as_bytes());
+        let _ = self.writer.flush();
+    }
+}
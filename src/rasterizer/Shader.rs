@@ -0,0 +1,91 @@
+use crate::matrices::Matrix44::*;
+use crate::vectors::Vector4::*;
+use crate::pipeline::Clipper::*;
+use crate::rasterizer::{Color::*, Fragment::*};
+use crate::mesh::Mesh::Vertex;
+
+// Runs once per vertex of a draw call, turning the caller's vertex type into
+// a clip-space position plus the varyings interpolated for the fragments.
+pub trait VertexShader {
+    type Input;
+    type Uniforms;
+
+    fn shade(&self, input: &Self::Input, uniforms: &Self::Uniforms) -> ClipVertex;
+}
+
+// Runs once per covered cell with the interpolated varyings and returns
+// what to draw, or None to discard the fragment.
+pub trait FragmentShader {
+    type Uniforms;
+
+    fn shade(&self, fragment: &Fragment, uniforms: &Self::Uniforms) -> Option<Shade>;
+}
+
+// Uniforms shared by the basic shaders.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BasicUniforms {
+    pub modelViewProjection: Matrix44,
+    pub color: Color
+}
+
+impl BasicUniforms {
+    pub fn new(modelViewProjection: &Matrix44, color: Color) -> BasicUniforms {
+        BasicUniforms {
+            modelViewProjection: *modelViewProjection,
+            color
+        }
+    }
+}
+
+// Transforms model-space positions by the model-view-projection matrix and
+// passes no varyings on.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct BasicVertexShader;
+
+impl VertexShader for BasicVertexShader {
+    type Input = Vector4;
+    type Uniforms = BasicUniforms;
+
+    fn shade(&self, input: &Vector4, uniforms: &BasicUniforms) -> ClipVertex {
+        ClipVertex::new(uniforms.modelViewProjection * *input, Vec::new())
+    }
+}
+
+// Fills every fragment with the uniform color at full intensity.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct BasicFragmentShader;
+
+impl FragmentShader for BasicFragmentShader {
+    type Uniforms = BasicUniforms;
+
+    fn shade(&self, _fragment: &Fragment, uniforms: &BasicUniforms) -> Option<Shade> {
+        Some(Shade::new(uniforms.color, 1.0))
+    }
+}
+
+// Transforms mesh vertices like BasicVertexShader and tints the uniform
+// color with the interpolated vertex colors. Used by Rasterizer::drawMesh.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct VertexColorShader;
+
+impl VertexShader for VertexColorShader {
+    type Input = Vertex;
+    type Uniforms = BasicUniforms;
+
+    fn shade(&self, input: &Vertex, uniforms: &BasicUniforms) -> ClipVertex {
+        let p = &input.position;
+
+        ClipVertex::new(uniforms.modelViewProjection * Vector4::new(p.x, p.y, p.z, 1.0), input.color.toFloats().to_vec())
+    }
+}
+
+impl FragmentShader for VertexColorShader {
+    type Uniforms = BasicUniforms;
+
+    fn shade(&self, fragment: &Fragment, uniforms: &BasicUniforms) -> Option<Shade> {
+        let v = &fragment.varyings;
+        let tint = Color::fromFloats(v[0], v[1], v[2]);
+
+        Some(Shade::new(uniforms.color.modulate(&tint), 1.0))
+    }
+}
@@ -8,7 +8,7 @@ pub struct Vector2 {
 }
 
 impl Vector2 {
-    fn new(x: f64, y: f64) -> Vector2 {
+    pub fn new(x: f64, y: f64) -> Vector2 {
         Vector2 {
             x,
             y
@@ -58,23 +58,23 @@ impl VectorProperties for Vector2 {
     }
 
     fn angle(vec1: &Vector2, vec2: &Vector2) -> f64 {
-        let dot = Vector2::dot(&vec1, &vec2);
+        let dot = Vector2::dot(vec1, vec2);
         let f = dot/(vec1.length() * vec2.length());
 
-        return f.acos()
+        f.acos()
     }
 
     fn dist(vec1: &Vector2, vec2: &Vector2) -> f64 {
         let x: f64 = vec1.x - vec2.x;
         let y: f64 = vec1.y - vec2.y;
 
-        return (x*x + y*y).sqrt()
+        (x*x + y*y).sqrt()
     }
 
     fn getNormalized(v: &Vector2) -> Vector2 {
         let mut f: Vector2 = *v;
         f.normalize();
-        return f;
+        f
     }
 
     fn scale(&mut self, factor: f64) {
@@ -105,7 +105,7 @@ impl ops::Add<Vector2> for Vector2 {
 
 impl ops::AddAssign<Vector2> for Vector2 {
     fn add_assign(&mut self, rhs: Vector2) {
-        *self = Vector2::add(&self, &rhs);
+        *self = Vector2::add(self, &rhs);
     }
 }
 
@@ -119,7 +119,7 @@ impl ops::Sub<Vector2> for Vector2 {
 
 impl ops::SubAssign<Vector2> for Vector2 {
     fn sub_assign(&mut self, rhs: Vector2) {
-        *self = Vector2::sub(&self, &rhs)
+        *self = Vector2::sub(self, &rhs)
     }
 }
 
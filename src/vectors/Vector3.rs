@@ -76,10 +76,10 @@ impl VectorProperties for Vector3 {
     }
 
     fn angle(vec1: &Vector3, vec2: &Vector3) -> f64 {
-        let dot = Vector3::dot(&vec1, &vec2);
+        let dot = Vector3::dot(vec1, vec2);
         let f = dot/(vec1.length() * vec2.length());
 
-        return f.acos()
+        f.acos()
     }
 
     fn dist(vec1: &Vector3, vec2: &Vector3) -> f64 {
@@ -87,13 +87,13 @@ impl VectorProperties for Vector3 {
         let y: f64 = vec1.y - vec2.y;
         let z: f64 = vec1.z - vec2.z;
 
-        return (x*x + y*y + z*z).sqrt()
+        (x*x + y*y + z*z).sqrt()
     }
 
     fn getNormalized(v: &Vector3) -> Vector3 {
         let mut f: Vector3 = *v;
         f.normalize();
-        return f;
+        f
     }
 
     fn scale(&mut self, factor: f64) {
@@ -126,7 +126,7 @@ impl ops::Add<Vector3> for Vector3 {
 
 impl ops::AddAssign<Vector3> for Vector3 {
     fn add_assign(&mut self, rhs: Vector3) {
-        *self = Vector3::add(&self, &rhs);
+        *self = Vector3::add(self, &rhs);
     }
 }
 
@@ -140,7 +140,7 @@ impl ops::Sub<Vector3> for Vector3 {
 
 impl ops::SubAssign<Vector3> for Vector3 {
     fn sub_assign(&mut self, rhs: Vector3) {
-        *self = Vector3::sub(&self, &rhs)
+        *self = Vector3::sub(self, &rhs)
     }
 }
 
@@ -162,6 +162,6 @@ impl ops::Rem<Vector3> for Vector3 {
 
 impl ops::RemAssign<Vector3> for Vector3 {
     fn rem_assign(&mut self, rhs: Vector3) {
-        *self = Vector3::cross(&self, &rhs)
+        *self = Vector3::cross(self, &rhs)
     }
 }
\ No newline at end of file
@@ -11,12 +11,12 @@ pub struct Vector4 {
 }
 
 impl Vector4 {
-    fn new(x: f64, y: f64, z: f64, w: f64) -> Vector4 {
+    pub fn new(x: f64, y: f64, z: f64, w: f64) -> Vector4 {
         Vector4 {
-            x: x,
-            y: y,
-            z: z,
-            w: w
+            x,
+            y,
+            z,
+            w
         }
     }
 }
@@ -72,10 +72,10 @@ impl VectorProperties for Vector4 {
     }
 
     fn angle(vec1: &Vector4, vec2: &Vector4) -> f64 {
-        let dot = Vector4::dot(&vec1, &vec2);
+        let dot = Vector4::dot(vec1, vec2);
         let f = dot/(vec1.length() * vec2.length());
 
-        return f.acos()
+        f.acos()
     }
 
     fn dist(vec1: &Vector4, vec2: &Vector4) -> f64 {
@@ -84,13 +84,13 @@ impl VectorProperties for Vector4 {
         let z: f64 = vec1.z - vec2.z;
         let w: f64 = vec1.w - vec2.w;
 
-        return (x*x + y*y + z*z + w*w).sqrt()
+        (x*x + y*y + z*z + w*w).sqrt()
     }
 
     fn getNormalized(v: &Vector4) -> Vector4 {
         let mut f: Vector4 = *v;
         f.normalize();
-        return f;
+        f
     }
 
     fn scale(&mut self, factor: f64) {
@@ -126,7 +126,7 @@ impl ops::Add<Vector4> for Vector4 {
 
 impl ops::AddAssign<Vector4> for Vector4 {
     fn add_assign(&mut self, rhs: Vector4) {
-        *self = Vector4::add(&self, &rhs);
+        *self = Vector4::add(self, &rhs);
     }
 }
 
@@ -140,7 +140,7 @@ impl ops::Sub<Vector4> for Vector4 {
 
 impl ops::SubAssign<Vector4> for Vector4 {
     fn sub_assign(&mut self, rhs: Vector4) {
-        *self = Vector4::sub(&self, &rhs)
+        *self = Vector4::sub(self, &rhs)
     }
 }
 
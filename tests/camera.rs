@@ -0,0 +1,168 @@
+#![allow(non_snake_case)]
+extern crate ncurses_rendering_engine;
+extern crate pancurses;
+
+use pancurses::Input;
+
+use ncurses_rendering_engine::camera::{Camera::*, Controllers::*};
+use ncurses_rendering_engine::matrices::Matrix44::*;
+use ncurses_rendering_engine::vectors::{Vector3::*, Vector4::*};
+use ncurses_rendering_engine::math_utils::GeneralMath;
+
+fn assertVectorNear(actual: Vector3, expected: Vector3) {
+    let d = actual - expected;
+    assert!(d.x.abs() < 1e-9 && d.y.abs() < 1e-9 && d.z.abs() < 1e-9, "{:?} != {:?}", actual, expected);
+}
+
+// Through the camera's view and projection, divided by w.
+fn toNdc(camera: &Camera, p: Vector3) -> Vector3 {
+    let clip = camera.getProjectionMatrix() * (camera.getViewMatrix() * Vector4::new(p.x, p.y, p.z, 1.0));
+    Vector3::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w)
+}
+
+fn perspectiveCamera(position: Vector3) -> Camera {
+    Camera::new(position, Projection::Perspective {
+        fovY: GeneralMath::toRadians(90.0),
+        near: 1.0,
+        far: 9.0
+    }, 2.0)
+}
+
+#[test]
+fn camera_projects_points_to_ndc() {
+    let mut camera = perspectiveCamera(Vector3::new(0.0, 0.0, 5.0));
+    camera.lookAt(&Vector3::new(0.0, 0.0, 0.0));
+
+    assertVectorNear(toNdc(&camera, Vector3::new(0.0, 0.0, 4.0)), Vector3::new(0.0, 0.0, -1.0));
+    assertVectorNear(toNdc(&camera, Vector3::new(18.0, 9.0, -4.0)), Vector3::new(1.0, 1.0, 1.0));
+    assertVectorNear(toNdc(&camera, Vector3::new(-2.0, 1.0, 4.0)), Vector3::new(-1.0, 1.0, -1.0));
+
+    // Orthographic, `height` spans y and the aspect widens x.
+    camera.setProjection(&Projection::Orthographic { height: 4.0, near: 1.0, far: 9.0 });
+    assertVectorNear(toNdc(&camera, Vector3::new(4.0, -2.0, 0.0)), Vector3::new(1.0, -1.0, 0.0));
+}
+
+#[test]
+fn camera_looking_along_up_stays_defined() {
+    let mut camera = perspectiveCamera(Vector3::new(0.0, 5.0, 0.0));
+    camera.lookAt(&Vector3::new(0.0, 0.0, 0.0));
+
+    assertVectorNear(camera.getRight(), Vector3::new(1.0, 0.0, 0.0));
+    assertVectorNear(toNdc(&camera, Vector3::new(0.0, 0.0, 0.0)), Vector3::new(0.0, 0.0, toNdc(&camera, Vector3::new(0.0, 0.0, 0.0)).z));
+    assert!(toNdc(&camera, Vector3::new(0.0, 0.0, -1.0)).y > 0.0);
+
+    let view: Matrix44 = camera.getViewMatrix();
+    assert!([view.m00, view.m11, view.m22, view.m30, view.m31, view.m32].iter().all(|m| m.is_finite()));
+}
+
+#[test]
+fn orbit_controller_circles_the_target() {
+    let target = Vector3::new(1.0, 0.0, 0.0);
+    let mut camera = perspectiveCamera(Vector3::new(0.0, 0.0, 0.0));
+    let mut orbit = OrbitController::new(target, 3.0);
+
+    orbit.apply(&mut camera);
+    assertVectorNear(*camera.getPosition(), Vector3::new(1.0, 0.0, 3.0));
+    assertVectorNear(camera.getForward(), Vector3::new(0.0, 0.0, -1.0));
+
+    // A quarter turn to the left puts the camera on the -x side.
+    orbit.rotate(GeneralMath::toRadians(90.0), 0.0);
+    orbit.apply(&mut camera);
+    assertVectorNear(*camera.getPosition(), Vector3::new(-2.0, 0.0, 0.0));
+    assertVectorNear(toNdc(&camera, target), Vector3::new(0.0, 0.0, toNdc(&camera, target).z));
+
+    // Keys rotate and zoom, distance stops at the minimum.
+    assert!(orbit.handleInput(&Input::KeyLeft, &mut camera));
+    let angle = GeneralMath::toRadians(95.0);
+    assertVectorNear(camera.getForward(), Vector3::new(angle.sin(), 0.0, -angle.cos()));
+
+    assert!(orbit.handleInput(&Input::Character('+'), &mut camera));
+    assert_eq!(orbit.distance, 2.75);
+    orbit.zoom(-10.0);
+    assert_eq!(orbit.distance, orbit.minDistance);
+
+    assert!(!orbit.handleInput(&Input::Character('x'), &mut camera));
+}
+
+#[test]
+fn orbit_controller_turns_over_the_poles() {
+    let target = Vector3::new(0.0, 1.0, 0.0);
+    let mut camera = perspectiveCamera(Vector3::new(0.0, 0.0, 0.0));
+    let mut orbit = OrbitController::new(target, 2.0);
+
+    // Pitch is not clamped: half a turn down goes over the top to the far
+    // side, upside down.
+    orbit.rotate(0.0, GeneralMath::toRadians(-90.0));
+    orbit.apply(&mut camera);
+    assertVectorNear(*camera.getPosition(), Vector3::new(0.0, 3.0, 0.0));
+    assertVectorNear(camera.getForward(), Vector3::new(0.0, -1.0, 0.0));
+
+    orbit.rotate(0.0, GeneralMath::toRadians(-90.0));
+    orbit.apply(&mut camera);
+    assertVectorNear(*camera.getPosition(), Vector3::new(0.0, 1.0, -2.0));
+    assertVectorNear(camera.getUp(), Vector3::new(0.0, -1.0, 0.0));
+
+    // Yaw turns about the view's up, which now points down.
+    orbit.rotate(GeneralMath::toRadians(90.0), 0.0);
+    orbit.apply(&mut camera);
+    assertVectorNear(*camera.getPosition(), Vector3::new(-2.0, 1.0, 0.0));
+    assertVectorNear(toNdc(&camera, target), Vector3::new(0.0, 0.0, toNdc(&camera, target).z));
+}
+
+#[test]
+fn orbit_controller_drags_the_arcball() {
+    let mut camera = perspectiveCamera(Vector3::new(0.0, 0.0, 0.0));
+    let mut orbit = OrbitController::new(Vector3::new(0.0, 0.0, 0.0), 3.0);
+
+    // Dragging from the center to the right rim turns the scene a quarter
+    // to the right, so the camera ends up on its left.
+    orbit.drag((0.0, 0.0), (1.0, 0.0));
+    orbit.apply(&mut camera);
+    assertVectorNear(*camera.getPosition(), Vector3::new(-3.0, 0.0, 0.0));
+
+    // Points past the rim are pulled onto it, and dragging back undoes it.
+    orbit.drag((2.0, 0.0), (0.0, 0.0));
+    orbit.apply(&mut camera);
+    assertVectorNear(*camera.getPosition(), Vector3::new(0.0, 0.0, 3.0));
+    assertVectorNear(camera.getUp(), Vector3::new(0.0, 1.0, 0.0));
+
+    // Dragging up rolls the near side of the scene upwards, so the camera
+    // drops below it.
+    orbit.drag((0.0, -0.5), (0.0, 0.5));
+    orbit.apply(&mut camera);
+    assert!(camera.getPosition().y < 0.0);
+    let center = Vector3::new(0.0, 0.0, 0.0);
+    assertVectorNear(toNdc(&camera, center), Vector3::new(0.0, 0.0, toNdc(&camera, center).z));
+
+    // No movement, no turn.
+    let before = orbit.orientation;
+    orbit.drag((0.25, 0.25), (0.25, 0.25));
+    assert_eq!(orbit.orientation, before);
+}
+
+#[test]
+fn first_person_controller_walks_along_its_heading() {
+    let mut camera = perspectiveCamera(Vector3::new(0.0, 1.0, 0.0));
+    let mut fly = FirstPersonController::default();
+
+    assert!(fly.handleInput(&Input::Character('w'), &mut camera));
+    assertVectorNear(*camera.getPosition(), Vector3::new(0.0, 1.0, -0.25));
+
+    // Turned right a quarter and looking up, walking stays level.
+    fly.turn(GeneralMath::toRadians(90.0), GeneralMath::toRadians(30.0));
+    fly.apply(&mut camera);
+    assertVectorNear(camera.getRight(), Vector3::new(0.0, 0.0, 1.0));
+
+    fly.translate(&mut camera, 1.0, 0.0, 0.0);
+    assertVectorNear(*camera.getPosition(), Vector3::new(1.0, 1.0, -0.25));
+
+    fly.translate(&mut camera, 0.0, 2.0, 0.0);
+    assertVectorNear(*camera.getPosition(), Vector3::new(1.0, 1.0, 1.75));
+
+    assert!(fly.handleInput(&Input::Character('r'), &mut camera));
+    assertVectorNear(*camera.getPosition(), Vector3::new(1.0, 1.25, 1.75));
+
+    assert!(fly.handleInput(&Input::KeyUp, &mut camera));
+    assert!((fly.pitch - GeneralMath::toRadians(35.0)).abs() < 1e-9);
+    assert!(!fly.handleInput(&Input::Character('q'), &mut camera));
+}
@@ -0,0 +1,76 @@
+#![allow(non_snake_case)]
+extern crate ncurses_rendering_engine;
+
+use ncurses_rendering_engine::rasterizer::{Color::*, FrameBuffer::*, RenderTarget::*};
+
+#[test]
+fn ansi8_ignores_brightness() {
+    assert_eq!(Color::new(0, 0, 0).toAnsi8(), 0);
+    assert_eq!(Color::new(200, 20, 20).toAnsi8(), 1);
+    assert_eq!(Color::new(60, 0, 0).toAnsi8(), 1);
+    assert_eq!(Color::new(0, 90, 90).toAnsi8(), 6);
+    assert_eq!(Color::WHITE.toAnsi8(), 7);
+}
+
+#[test]
+fn xterm256_picks_cube_or_gray() {
+    assert_eq!(Color::new(255, 0, 0).toXterm256(), 196);
+    assert_eq!(Color::new(0, 0, 255).toXterm256(), 21);
+    assert_eq!(Color::new(128, 128, 128).toXterm256(), 244);
+    assert_eq!(Color::WHITE.toXterm256(), 231);
+}
+
+#[test]
+fn detects_mode_from_environment() {
+    assert_eq!(ColorMode::fromEnv("truecolor", "xterm-256color"), ColorMode::TrueColor);
+    assert_eq!(ColorMode::fromEnv("", "xterm-256color"), ColorMode::Xterm256);
+    assert_eq!(ColorMode::fromEnv("", "xterm"), ColorMode::Ansi8);
+    assert_eq!(ColorMode::fromEnv("", "dumb"), ColorMode::Monochrome);
+}
+
+#[test]
+fn ansi_target_encodes_truecolor() {
+    let mut fb = FrameBuffer::new(2, 1);
+    fb.setCell(0, 0, Cell { glyph: '#', color: Color::new(10, 20, 30), depth: 0.5, ..Default::default() });
+
+    let mut target = AnsiTarget::withColorMode(Vec::new(), 2, 1, ColorMode::TrueColor);
+    fb.present(&mut target);
+
+    let written = String::from_utf8(target.getWriter().clone()).unwrap();
+    assert_eq!(written, "\x1b[1;1H\x1b[38;2;10;20;30m\x1b[49m#\x1b[38;2;255;255;255m \x1b[0m");
+}
+
+#[test]
+fn ansi_target_only_switches_colors_on_change() {
+    let red = Cell { glyph: '#', color: Color::new(200, 20, 20), depth: 0.5, ..Default::default() };
+    let teal = Color::new(0, 90, 90);
+
+    let mut target = AnsiTarget::withColorMode(Vec::new(), 3, 1, ColorMode::Ansi8);
+    target.putCell(0, 0, &red);
+    target.putCell(1, 0, &Cell { background: Some(teal), ..red });
+
+    // Cells past the edge are dropped.
+    target.putCell(3, 0, &red);
+    target.putCell(0, -1, &red);
+
+    let frame = target.encodeFrame();
+    assert_eq!(frame, "\x1b[1;1H\x1b[31m\x1b[49m#\x1b[46m#\x1b[37m\x1b[49m \x1b[0m");
+
+    // Every flush writes the whole frame again.
+    target.flush();
+    target.flush();
+    assert_eq!(String::from_utf8(target.getWriter().clone()).unwrap(), format!("{}{}", frame, frame));
+}
+
+#[test]
+fn ansi_target_monochrome_writes_glyphs_only() {
+    let mut fb = FrameBuffer::new(2, 2);
+    fb.setCell(0, 0, Cell { glyph: '#', color: Color::new(200, 20, 20), depth: 0.5, ..Default::default() });
+    fb.setCell(1, 1, Cell { glyph: '.', background: Some(Color::WHITE), depth: 0.5, ..Default::default() });
+
+    let mut target = AnsiTarget::withColorMode(Vec::new(), 2, 2, ColorMode::Monochrome);
+    fb.present(&mut target);
+
+    assert_eq!(target.getSize(), (2, 2));
+    assert_eq!(String::from_utf8(target.getWriter().clone()).unwrap(), "\x1b[1;1H# \x1b[2;1H .");
+}
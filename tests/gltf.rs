@@ -0,0 +1,321 @@
+#![allow(non_snake_case)]
+extern crate ncurses_rendering_engine;
+
+use std::fs;
+
+use ncurses_rendering_engine::loaders::{LoadError::*, Gltf::*};
+use ncurses_rendering_engine::rasterizer::Color::*;
+use ncurses_rendering_engine::vectors::{Vector3::*, Vector4::*};
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::new();
+
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let bits = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for k in 0..4 {
+            if k <= chunk.len() {
+                text.push(ALPHABET[(bits >> (18 - 6 * k) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+
+    text
+}
+
+fn floats(bytes: &mut Vec<u8>, values: &[f32]) {
+    for v in values.iter() {
+        bytes.extend_from_slice(&v.to_le_bytes());
+    }
+}
+
+// A skinned triangle under a translated root, with a joint animated by a
+// linear translation and a stepped rotation. Returns the JSON with `BUFFER`
+// standing in for the buffer entry, and the binary data.
+fn fixture() -> (String, Vec<u8>) {
+    let mut bin: Vec<u8> = Vec::new();
+    floats(&mut bin, &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+    for i in [0u16, 1, 2].iter() {
+        bin.extend_from_slice(&i.to_le_bytes());
+    }
+    bin.extend_from_slice(&[0, 0]);
+    floats(&mut bin, &[0.0, 2.0]);
+    floats(&mut bin, &[0.0, 0.0, 0.0, 4.0, 0.0, 0.0]);
+    floats(&mut bin, &[1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, -1.0, 0.0, 0.0, 1.0]);
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    floats(&mut bin, &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, half, half]);
+    assert_eq!(bin.len(), 172);
+
+    let json = r#"{
+  "asset": { "version": "2.0" },
+  "buffers": [ BUFFER ],
+  "bufferViews": [
+    { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+    { "buffer": 0, "byteOffset": 36, "byteLength": 6 },
+    { "buffer": 0, "byteOffset": 44, "byteLength": 8 },
+    { "buffer": 0, "byteOffset": 52, "byteLength": 24 },
+    { "buffer": 0, "byteOffset": 76, "byteLength": 64 },
+    { "buffer": 0, "byteOffset": 140, "byteLength": 32 }
+  ],
+  "accessors": [
+    { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" },
+    { "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" },
+    { "bufferView": 2, "componentType": 5126, "count": 2, "type": "SCALAR" },
+    { "bufferView": 3, "componentType": 5126, "count": 2, "type": "VEC3" },
+    { "bufferView": 4, "componentType": 5126, "count": 1, "type": "MAT4" },
+    { "bufferView": 5, "componentType": 5126, "count": 2, "type": "VEC4" }
+  ],
+  "materials": [
+    { "name": "orange", "pbrMetallicRoughness": { "baseColorFactor": [1, 0.5, 0, 1], "baseColorTexture": { "index": 0 } } }
+  ],
+  "textures": [ { "source": 0 } ],
+  "images": [ { "uri": "orange%20skin.png" } ],
+  "meshes": [
+    { "name": "tri", "primitives": [ { "attributes": { "POSITION": 0 }, "indices": 1, "material": 0 } ] }
+  ],
+  "nodes": [
+    { "name": "root", "translation": [0, 0, -5], "children": [1, 2] },
+    { "name": "body", "mesh": 0, "skin": 0, "scale": [2, 2, 2] },
+    { "name": "joint", "translation": [1, 0, 0] }
+  ],
+  "skins": [ { "joints": [2], "inverseBindMatrices": 4 } ],
+  "scenes": [ { "nodes": [0] } ],
+  "scene": 0,
+  "animations": [
+    {
+      "name": "wave",
+      "samplers": [
+        { "input": 2, "output": 3 },
+        { "input": 2, "output": 5, "interpolation": "STEP" }
+      ],
+      "channels": [
+        { "sampler": 0, "target": { "node": 2, "path": "translation" } },
+        { "sampler": 1, "target": { "node": 2, "path": "rotation" } }
+      ]
+    }
+  ]
+}"#;
+
+    (json.to_string(), bin)
+}
+
+fn embedded() -> Vec<u8> {
+    let (json, bin) = fixture();
+    let buffer = format!(r#"{{ "byteLength": {}, "uri": "data:application/octet-stream;base64,{}" }}"#, bin.len(), base64(&bin));
+    json.replace("BUFFER", &buffer).into_bytes()
+}
+
+fn glb() -> Vec<u8> {
+    let (json, mut bin) = fixture();
+    let mut json = json.replace("BUFFER", &format!(r#"{{ "byteLength": {} }}"#, bin.len())).into_bytes();
+
+    while json.len() % 4 != 0 {
+        json.push(b' ');
+    }
+
+    while bin.len() % 4 != 0 {
+        bin.push(0);
+    }
+
+    let mut bytes: Vec<u8> = Vec::new();
+    bytes.extend_from_slice(b"glTF");
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    bytes.extend_from_slice(&((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+    bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(b"JSON");
+    bytes.extend_from_slice(&json);
+    bytes.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(b"BIN\0");
+    bytes.extend_from_slice(&bin);
+    bytes
+}
+
+fn transform(m: &ncurses_rendering_engine::matrices::Matrix44::Matrix44, p: Vector3) -> Vector3 {
+    let v = *m * Vector4::new(p.x, p.y, p.z, 1.0);
+    Vector3::new(v.x, v.y, v.z)
+}
+
+fn assertNear(a: Vector3, b: Vector3) {
+    assert!((a.x - b.x).abs() < 1e-6 && (a.y - b.y).abs() < 1e-6 && (a.z - b.z).abs() < 1e-6, "{:?} != {:?}", a, b);
+}
+
+fn assertSameModel(model: &GltfModel) {
+    let primitive = &model.meshes[0].primitives[0];
+    assert_eq!(model.meshes[0].name.as_deref(), Some("tri"));
+    assert_eq!(primitive.mesh.getIndices(), &[0, 1, 2]);
+    assert_eq!(primitive.mesh.getVertices()[1].position, Vector3::new(1.0, 0.0, 0.0));
+    assert_eq!(primitive.material, Some(0));
+
+    let material = &model.materials[0];
+    assert_eq!(material.material.name, "orange");
+    assert_eq!(material.baseColorFactor, [1.0, 0.5, 0.0, 1.0]);
+    assert_eq!(material.material.texture.as_deref(), Some("orange%20skin.png"));
+    assert_eq!(primitive.mesh.getVertices()[0].color, Color::fromFloats(1.0, 0.5, 0.0));
+
+    assert_eq!(model.scenes, vec![vec![0]]);
+    assert_eq!(model.nodes[0].children, vec![1, 2]);
+}
+
+#[test]
+fn gltf_embedded_buffers_and_glb_agree() {
+    let embedded = GltfLoader::parse(&embedded()).unwrap();
+    let binary = GltfLoader::parse(&glb()).unwrap();
+
+    assertSameModel(&embedded);
+    assertSameModel(&binary);
+    assert_eq!(embedded, binary);
+}
+
+#[test]
+fn gltf_node_hierarchy_composes_trs() {
+    let model = GltfLoader::parse(&embedded()).unwrap();
+    let world = model.getWorldMatrices();
+
+    assertNear(transform(&world[1], Vector3::new(1.0, 0.0, 0.0)), Vector3::new(2.0, 0.0, -5.0));
+    assertNear(transform(&world[2], Vector3::new(0.0, 0.0, 0.0)), Vector3::new(1.0, 0.0, -5.0));
+
+    let baked = model.toMesh();
+    assert_eq!(baked.getTriangleCount(), 1);
+    assertNear(baked.getVertices()[2].position, Vector3::new(0.0, 2.0, -5.0));
+}
+
+#[test]
+fn gltf_skins_and_animation_channels() {
+    let mut model = GltfLoader::parse(&embedded()).unwrap();
+
+    let skin = &model.skins[0];
+    assert_eq!(skin.joints, vec![2]);
+    assert_eq!(model.nodes[1].skin, Some(0));
+
+    // The joint sits at x = 1 in the bind pose, so its inverse bind matrix
+    // cancels that offset.
+    let joints = model.getJointMatrices(0, &model.getWorldMatrices());
+    assertNear(transform(&joints[0], Vector3::new(0.0, 0.0, 0.0)), Vector3::new(0.0, 0.0, -5.0));
+
+    let animation = model.animations[0].clone();
+    assert_eq!(animation.name.as_deref(), Some("wave"));
+    assert_eq!(animation.getDuration(), 2.0);
+
+    let translation = &animation.channels[0];
+    assert_eq!(translation.path, AnimationPath::Translation);
+    assert_eq!(translation.sample(1.0), vec![2.0, 0.0, 0.0]);
+    assert_eq!(translation.sample(-1.0), vec![0.0, 0.0, 0.0]);
+    assert_eq!(translation.sample(5.0), vec![4.0, 0.0, 0.0]);
+
+    let rotation = &animation.channels[1];
+    assert_eq!(rotation.interpolation, Interpolation::Step);
+    assert_eq!(rotation.sample(1.9), vec![0.0, 0.0, 0.0, 1.0]);
+
+    animation.apply(2.0, &mut model.nodes);
+    let world = model.getWorldMatrices();
+
+    // Moved to x = 4 and turned a quarter about z.
+    assertNear(transform(&world[2], Vector3::new(1.0, 0.0, 0.0)), Vector3::new(4.0, 1.0, -5.0));
+
+    // A channel emptied by hand has no value and leaves its node alone.
+    let mut empty = animation.clone();
+    empty.channels[0].times.clear();
+    assert_eq!(empty.channels[0].sample(1.0), Vec::<f64>::new());
+    empty.apply(2.0, &mut model.nodes);
+    assertNear(transform(&model.getWorldMatrices()[2], Vector3::new(1.0, 0.0, 0.0)), Vector3::new(4.0, 1.0, -5.0));
+}
+
+#[test]
+fn gltf_load_reads_neighbouring_buffers() {
+    let dir = std::env::temp_dir().join(format!("ncurses-engine-gltf-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let (json, bin) = fixture();
+    fs::write(dir.join("scene data.bin"), &bin).unwrap();
+    fs::write(dir.join("scene.gltf"), json.replace("BUFFER", &format!(r#"{{ "byteLength": {}, "uri": "scene%20data.bin" }}"#, bin.len()))).unwrap();
+
+    assertSameModel(&GltfLoader::load(dir.join("scene.gltf")).unwrap());
+
+    // Without a directory only embedded buffers can be used.
+    match GltfLoader::parse(&fs::read(dir.join("scene.gltf")).unwrap()) {
+        Err(LoadError::Format(message)) => assert!(message.contains("scene%20data.bin"), "{}", message),
+        other => panic!("expected a format error, got {:?}", other)
+    }
+}
+
+#[test]
+fn gltf_reports_errors() {
+    match GltfLoader::parse(b"{\n  \"asset\": { \"version\": \"2.0\" },\n  \"nodes\": [ }\n}") {
+        Err(LoadError::Parse { line, .. }) => assert_eq!(line, 3),
+        other => panic!("expected a parse error, got {:?}", other)
+    }
+
+    let nested = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+
+    match GltfLoader::parse(nested.as_bytes()) {
+        Err(LoadError::Parse { message, .. }) => assert!(message.contains("nested"), "{}", message),
+        other => panic!("expected a parse error, got {:?}", other)
+    }
+
+    let failures: [(&str, &str); 4] = [
+        (r#"{ "asset": { "version": "1.0" } }"#, "version 1.0"),
+        (r#"{ "asset": { "version": "2.0" }, "extensionsRequired": ["KHR_draco_mesh_compression"] }"#, "KHR_draco_mesh_compression"),
+        (r#"{ "asset": { "version": "2.0" }, "nodes": [ { "children": [1] }, { "children": [0] } ] }"#, "cycle"),
+        (r#"{ "asset": { "version": "2.0" }, "meshes": [ { "primitives": [ { "attributes": {} } ] } ] }"#, "meshes[0].primitives[0]: POSITION")
+    ];
+
+    for (source, expected) in failures.iter() {
+        match GltfLoader::parse(source.as_bytes()) {
+            Err(LoadError::Format(message)) => assert!(message.contains(expected), "{}", message),
+            other => panic!("expected a format error for {}, got {:?}", source, other)
+        }
+    }
+}
+
+#[test]
+fn gltf_rejects_accessors_outside_their_buffers() {
+    let json = String::from_utf8(embedded()).unwrap();
+    let huge = "18446744073709551615";
+
+    let failures = [
+        (r#""count": 3, "type": "VEC3""#, r#""count": 1e18, "type": "VEC3""#.to_string(), "accessors[0]: 1000000000000000000 elements"),
+        (r#""bufferView": 0, "componentType": 5126, "count": 3"#, r#""componentType": 5126, "count": 1e18"#.to_string(), "accessors[0]: 1000000000000000000 elements"),
+        (r#""bufferView": 0, "componentType""#, format!(r#""bufferView": 0, "byteOffset": {}, "componentType""#, huge), "accessors[0]: 3 elements from byteOffset"),
+        (r#""byteOffset": 36, "byteLength": 6"#, format!(r#""byteOffset": {}, "byteLength": 6"#, huge), "bufferViews[1]: runs past the end")
+    ];
+
+    for (from, to, expected) in failures.iter() {
+        let source = json.replacen(from, to, 1);
+        assert_ne!(source, json);
+
+        match GltfLoader::parse(source.as_bytes()) {
+            Err(LoadError::Format(message)) => assert!(message.contains(expected), "{}", message),
+            other => panic!("expected a format error for {}, got {:?}", to, other)
+        }
+    }
+}
+
+#[test]
+fn gltf_rejects_bad_indices() {
+    let json = String::from_utf8(embedded()).unwrap();
+    let indices = r#"{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }"#;
+
+    // Unsigned bytes are fine, the first three of the short indices read as
+    // 0, 0 and 1.
+    let bytes = json.replacen(indices, &indices.replace("5123", "5121"), 1);
+    assert_eq!(GltfLoader::parse(bytes.as_bytes()).unwrap().meshes[0].primitives[0].mesh.getIndices(), &[0, 0, 1]);
+
+    let failures = [
+        (indices.replace("5123", "5122"), "meshes[0].primitives[0]: indices must be unsigned integers, not componentType 5122"),
+        (indices.replace("\"count\": 3", "\"count\": 2"), "meshes[0].primitives[0]: 2 indices do not make whole triangles")
+    ];
+
+    for (to, expected) in failures.iter() {
+        let source = json.replacen(indices, to, 1);
+        assert_ne!(source, json);
+
+        match GltfLoader::parse(source.as_bytes()) {
+            Err(LoadError::Format(message)) => assert!(message.contains(expected), "{}", message),
+            other => panic!("expected a format error for {}, got {:?}", to, other)
+        }
+    }
+}
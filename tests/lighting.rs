@@ -0,0 +1,246 @@
+#![allow(non_snake_case)]
+extern crate ncurses_rendering_engine;
+
+mod snapshot;
+
+use ncurses_rendering_engine::rasterizer::{Rasterizer::*, CharGrid::*, Color::*, GlyphRamp::*};
+use ncurses_rendering_engine::pipeline::Pipeline::*;
+use ncurses_rendering_engine::camera::Camera::*;
+use ncurses_rendering_engine::mesh::Primitives::*;
+use ncurses_rendering_engine::lighting::{Light::*, Lighting::*};
+use ncurses_rendering_engine::vectors::Vector3::*;
+use ncurses_rendering_engine::math_utils::GeneralMath;
+
+const WIDTH: i32 = 32;
+const HEIGHT: i32 = 16;
+
+fn present<V, F>(rast: &Rasterizer<V, F>) -> CharGrid {
+    let mut grid = CharGrid::new(WIDTH, HEIGHT);
+    rast.getFrameBuffer().present(&mut grid);
+    grid
+}
+
+fn cameraPipeline() -> Pipeline {
+    let mut camera = Camera::new(Vector3::new(0.0, 1.0, 3.0), Projection::Perspective {
+        fovY: GeneralMath::toRadians(60.0),
+        near: 0.5,
+        far: 100.0
+    }, GeneralMath::terminalAspect(WIDTH, HEIGHT));
+    camera.lookAt(&Vector3::new(0.0, 0.0, 0.0));
+
+    let mut pipeline = Pipeline::new(Viewport::new(0.0, 0.0, WIDTH as f64, HEIGHT as f64));
+    pipeline.setProjection(&camera.getProjectionMatrix());
+    pipeline.setView(&camera.getViewMatrix());
+    pipeline
+}
+
+fn assertNear(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+}
+
+fn diffuseOnly(light: Light) -> Lighting {
+    let mut lighting = Lighting::new();
+    lighting.setSpecular(0.0);
+    lighting.addLight(light);
+    lighting
+}
+
+const ORIGIN: Vector3 = Vector3 { x: 0.0, y: 0.0, z: 0.0 };
+const UP: Vector3 = Vector3 { x: 0.0, y: 1.0, z: 0.0 };
+const EYE: Vector3 = Vector3 { x: 0.0, y: 5.0, z: 0.0 };
+
+#[test]
+fn lambert_follows_the_angle_of_incidence() {
+    let straight = diffuseOnly(Light::Directional { direction: Vector3::new(0.0, -2.0, 0.0), color: Color::WHITE, intensity: 1.0 });
+    assertNear(straight.illuminate(&ORIGIN, &UP, &EYE).diffuse[0], 1.0);
+
+    // 60 degrees off the normal gives cos 60 = 0.5.
+    let angle = GeneralMath::toRadians(60.0);
+    let slanted = diffuseOnly(Light::Directional { direction: Vector3::new(-angle.sin(), -angle.cos(), 0.0), color: Color::WHITE, intensity: 1.0 });
+    assertNear(slanted.illuminate(&ORIGIN, &UP, &EYE).diffuse[0], 0.5);
+
+    let behind = diffuseOnly(Light::Directional { direction: UP, color: Color::WHITE, intensity: 1.0 });
+    assert_eq!(behind.illuminate(&ORIGIN, &UP, &EYE).diffuse, [0.0; 3]);
+}
+
+#[test]
+fn point_lights_fall_off_with_distance() {
+    let lighting = diffuseOnly(Light::Point {
+        position: Vector3::new(0.0, 2.0, 0.0),
+        color: Color::new(255, 0, 0),
+        intensity: 1.0,
+        attenuation: Attenuation::new(1.0, 0.0, 1.0)
+    });
+
+    let lit = lighting.illuminate(&ORIGIN, &UP, &EYE);
+    assertNear(lit.diffuse[0], 1.0 / 5.0);
+    assertNear(lit.diffuse[1], 0.0);
+    assertNear(Attenuation::default().factor(100.0), 1.0);
+}
+
+#[test]
+fn spot_lights_fade_between_cone_angles() {
+    let spot = |target: Vector3| diffuseOnly(Light::Spot {
+        position: Vector3::new(0.0, 1.0, 0.0),
+        direction: target - Vector3::new(0.0, 1.0, 0.0),
+        innerAngle: GeneralMath::toRadians(20.0),
+        outerAngle: GeneralMath::toRadians(40.0),
+        color: Color::WHITE,
+        intensity: 1.0,
+        attenuation: Default::default()
+    }).illuminate(&ORIGIN, &UP, &EYE).diffuse[0];
+
+    assertNear(spot(ORIGIN), 1.0);
+
+    // Aimed 30 degrees away, between the cone angles, the light fades with
+    // the cosine of the angle.
+    let t = GeneralMath::toRadians(30.0).tan();
+    let expected = (GeneralMath::toRadians(30.0).cos() - GeneralMath::toRadians(40.0).cos())
+        / (GeneralMath::toRadians(20.0).cos() - GeneralMath::toRadians(40.0).cos());
+    assertNear(spot(Vector3::new(t, 0.0, 0.0)), expected);
+
+    assertNear(spot(Vector3::new(2.0, 0.0, 0.0)), 0.0);
+}
+
+#[test]
+fn ambient_light_ignores_normals() {
+    let mut lighting = diffuseOnly(Light::Ambient { color: Color::WHITE, intensity: 0.25 });
+    lighting.addLight(Light::Directional { direction: Vector3::new(0.0, -1.0, 0.0), color: Color::WHITE, intensity: 1.0 });
+
+    assertNear(lighting.illuminate(&ORIGIN, &UP, &EYE).diffuse[2], 1.25);
+    assertNear(lighting.illuminate(&ORIGIN, &Vector3::new(0.0, -1.0, 0.0), &EYE).diffuse[2], 0.25);
+
+    // Without a normal only the ambient term applies.
+    assertNear(lighting.illuminate(&ORIGIN, &ORIGIN, &EYE).diffuse[2], 0.25);
+}
+
+#[test]
+fn specular_models_peak_at_the_mirror_direction() {
+    let mut lighting = Lighting::new();
+    lighting.setSpecular(1.0);
+    lighting.setShininess(16.0);
+    lighting.addLight(Light::Directional { direction: Vector3::new(1.0, -1.0, 0.0), color: Color::WHITE, intensity: 1.0 });
+
+    let mirror = Vector3::new(5.0, 5.0, 0.0);
+    let offAxis = Vector3::new(5.0, 2.0, 0.0);
+
+    lighting.setSpecularModel(SpecularModel::Phong);
+    assertNear(lighting.illuminate(&ORIGIN, &UP, &mirror).specular[0], 1.0);
+    let phong = lighting.illuminate(&ORIGIN, &UP, &offAxis).specular[0];
+
+    lighting.setSpecularModel(SpecularModel::BlinnPhong);
+    assertNear(lighting.illuminate(&ORIGIN, &UP, &mirror).specular[0], 1.0);
+    let blinn = lighting.illuminate(&ORIGIN, &UP, &offAxis).specular[0];
+
+    // The half vector moves half as far, so Blinn-Phong highlights spread wider.
+    assert!(phong > 0.0 && phong < blinn && blinn < 1.0, "phong {} blinn {}", phong, blinn);
+}
+
+#[test]
+fn illumination_sets_color_and_intensity() {
+    let lit = Illumination {
+        diffuse: [0.5, 0.5, 0.5],
+        specular: [0.1, 0.1, 0.1]
+    };
+
+    let shade = lit.shade(&Color::new(255, 0, 0));
+    assert_eq!(shade.color, Color::fromFloats(0.6, 0.1, 0.1));
+    assertNear(shade.intensity, 0.6);
+
+    // Drawn, the light level picks the glyph.
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    let mut pipeline = cameraPipeline();
+    pipeline.setView(&Default::default());
+    pipeline.setProjection(&Default::default());
+
+    let mut lighting = diffuseOnly(Light::Directional { direction: Vector3::new(0.0, 0.0, -1.0), color: Color::WHITE, intensity: 0.5 });
+    lighting.setShadingModel(ShadingModel::Flat);
+
+    let mut quad = Primitives::plane(2.0, 2.0, 1, 1);
+
+    // Stand the plane up to face the camera down -z.
+    for v in quad.getVerticesMut().iter_mut() {
+        v.position = Vector3::new(v.position.x, -v.position.z, 0.0);
+        v.normal = Vector3::new(0.0, 0.0, 1.0);
+    }
+
+    rast.drawLitMesh(&quad, &pipeline, &lighting);
+
+    let cell = rast.getFrameBuffer().getCell(WIDTH / 2, HEIGHT / 2).unwrap();
+    assert_eq!(cell.glyph, GlyphRamp::default().glyphFor(0.5));
+    assert_eq!(cell.color, Color::fromFloats(0.5, 0.5, 0.5));
+}
+
+#[test]
+fn gouraud_matches_phong_on_flat_surfaces() {
+    let pipeline = cameraPipeline();
+    let mesh = Primitives::plane(2.0, 2.0, 2, 2);
+    let mut lighting = diffuseOnly(Light::Directional { direction: Vector3::new(0.3, -1.0, -0.2), color: Color::WHITE, intensity: 0.8 });
+
+    let mut frames: Vec<CharGrid> = Vec::new();
+
+    for model in [ShadingModel::Flat, ShadingModel::Gouraud, ShadingModel::Phong].iter() {
+        let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+        lighting.setShadingModel(*model);
+        rast.drawLitMesh(&mesh, &pipeline, &lighting);
+        frames.push(present(&rast));
+    }
+
+    assert_eq!(frames[0], frames[1]);
+    assert_eq!(frames[1], frames[2]);
+}
+
+#[test]
+fn flat_shading_follows_the_front_face() {
+    let pipeline = cameraPipeline();
+    let lighting = {
+        let mut lighting = diffuseOnly(Light::Directional { direction: Vector3::new(0.3, -1.0, -0.2), color: Color::WHITE, intensity: 0.8 });
+        lighting.setShadingModel(ShadingModel::Flat);
+        lighting
+    };
+
+    let counterClockwise = Primitives::plane(2.0, 2.0, 2, 2);
+    let mut clockwise = counterClockwise.clone();
+
+    for triangle in clockwise.getIndicesMut().chunks_mut(3) {
+        triangle.swap(1, 2);
+    }
+
+    let mut expected = Rasterizer::new(WIDTH, HEIGHT);
+    expected.setCullMode(CullMode::Back);
+    expected.drawLitMesh(&counterClockwise, &pipeline, &lighting);
+
+    // The same plane wound the other way, with the front face to match, is
+    // lit from the same side.
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    rast.setCullMode(CullMode::Back);
+    rast.setFrontFace(FrontFace::Clockwise);
+    rast.drawLitMesh(&clockwise, &pipeline, &lighting);
+
+    let frame = present(&rast);
+    assert_eq!(frame, present(&expected));
+    assert!(frame.getGlyph(WIDTH / 2, HEIGHT / 2) != Some(' '));
+}
+
+fn litSphere(model: ShadingModel) -> CharGrid {
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    rast.setCullMode(CullMode::Back);
+
+    let mut lighting = Lighting::new();
+    lighting.setShadingModel(model);
+    lighting.addLight(Light::Ambient { color: Color::WHITE, intensity: 0.1 });
+    lighting.addLight(Light::Directional { direction: Vector3::new(1.0, -1.0, -1.0), color: Color::WHITE, intensity: 0.9 });
+
+    rast.drawLitMesh(&Primitives::uvSphere(1.2, 16, 8), &cameraPipeline(), &lighting);
+    present(&rast)
+}
+
+#[test]
+fn lit_sphere_flat() {
+    snapshot::assertSnapshot("lit_sphere_flat", &litSphere(ShadingModel::Flat));
+}
+
+#[test]
+fn lit_sphere_phong() {
+    snapshot::assertSnapshot("lit_sphere_phong", &litSphere(ShadingModel::Phong));
+}
@@ -0,0 +1,476 @@
+#![allow(non_snake_case)]
+extern crate ncurses_rendering_engine;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use ncurses_rendering_engine::loaders::{LoadError::*, Obj::*, Stl::*, Ply::*};
+use ncurses_rendering_engine::rasterizer::Color::*;
+use ncurses_rendering_engine::vectors::{Vector2::*, Vector3::*};
+
+// A scratch directory unique to one test.
+fn scratchDir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ncurses-engine-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn parseObj(source: &str) -> ObjModel {
+    ObjLoader::parse(source, &HashMap::new()).unwrap()
+}
+
+fn errorLine<T: std::fmt::Debug>(result: Result<T, LoadError>) -> (usize, String) {
+    match result {
+        Err(LoadError::Parse { line, message }) => (line, message),
+        other => panic!("expected a parse error, got {:?}", other)
+    }
+}
+
+#[test]
+fn obj_polygons_are_fan_triangulated() {
+    let model = parseObj("
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v -1 1 0
+f 1 2 3 4 5
+");
+
+    assert_eq!(model.mesh.getVertices().len(), 5);
+    assert_eq!(model.mesh.getIndices(), &[0, 1, 2, 0, 2, 3, 0, 3, 4]);
+}
+
+#[test]
+fn obj_corners_carry_uvs_and_normals() {
+    let model = parseObj("
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vt 0.25 0.75
+vn 0 0 1
+f 1/1/1 2//1 3/1
+f 1/1/1 3/1 2//1
+");
+
+    let vertices = model.mesh.getVertices();
+
+    // Corners repeated with the same indices share a vertex.
+    assert_eq!(vertices.len(), 3);
+    assert_eq!(model.mesh.getIndices(), &[0, 1, 2, 0, 2, 1]);
+
+    assert_eq!(vertices[0].uv, Vector2::new(0.25, 0.75));
+    assert_eq!(vertices[0].normal, Vector3::new(0.0, 0.0, 1.0));
+    assert_eq!(vertices[1].uv, Vector2::new(0.0, 0.0));
+    assert_eq!(vertices[1].normal, Vector3::new(0.0, 0.0, 1.0));
+    assert_eq!(vertices[2].normal, Vector3::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn obj_negative_indices_count_back() {
+    let model = parseObj("
+v 5 5 5
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f -3 -2 -1
+");
+
+    let positions: Vec<Vector3> = model.mesh.getVertices().iter().map(|v| v.position).collect();
+    assert_eq!(positions, vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)]);
+}
+
+#[test]
+fn obj_groups_objects_and_materials() {
+    let materials = ObjLoader::parseMaterials("
+# two materials
+newmtl red
+Kd 1 0 0
+newmtl textured
+Kd 0.5 0.5 0.5
+map_Kd -s 1 1 1 bricks.png
+").unwrap();
+
+    assert_eq!(materials["textured"].texture.as_deref(), Some("bricks.png"));
+
+    let model = ObjLoader::parse("
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+o house
+g walls
+usemtl red
+f 1 2 3
+f 2 4 3
+usemtl textured
+f 1 2 4
+g roof
+f 1 3 4
+o shed
+f 1 2 3
+", &materials).unwrap();
+
+    let summary: Vec<_> = model.groups.iter()
+        .map(|g| (g.object.as_deref(), g.name.as_deref(), g.material.as_deref(), g.start, g.count))
+        .collect();
+
+    assert_eq!(summary, vec![
+        (Some("house"), Some("walls"), Some("red"), 0, 6),
+        (Some("house"), Some("walls"), Some("textured"), 6, 3),
+        (Some("house"), Some("roof"), Some("textured"), 9, 3),
+        (Some("shed"), None, Some("textured"), 12, 3)
+    ]);
+
+    // The same position under two materials becomes two vertices.
+    let vertices = model.mesh.getVertices();
+    assert_eq!(vertices[0].color, Color::new(255, 0, 0));
+    assert_eq!(vertices[model.mesh.getIndices()[6] as usize].color, Color::new(128, 128, 128));
+}
+
+#[test]
+fn obj_reports_line_numbers() {
+    let (line, message) = errorLine(ObjLoader::parse("v 0 0 0\nv 1 x 0\n", &HashMap::new()));
+    assert_eq!(line, 2);
+    assert!(message.contains("'x'"), "{}", message);
+
+    let (line, message) = errorLine(ObjLoader::parse("v 0 0 0\nv 1 0 0\n\nf 1 2\n", &HashMap::new()));
+    assert_eq!(line, 4);
+    assert!(message.contains("at least 3"), "{}", message);
+
+    let (line, message) = errorLine(ObjLoader::parse("v 0 0 0\nf 1 1 2\n", &HashMap::new()));
+    assert_eq!(line, 2);
+    assert!(message.contains("out of range"), "{}", message);
+
+    let (line, _) = errorLine(ObjLoader::parse("v 0 0 0\nf 0 1 1\n", &HashMap::new()));
+    assert_eq!(line, 2);
+
+    let (line, message) = errorLine(ObjLoader::parse("v 0 0 0\nvn 0 0 1\nf 1//2 1 1\n", &HashMap::new()));
+    assert_eq!(line, 3);
+    assert!(message.contains("normal"), "{}", message);
+
+    let error = ObjLoader::parse("v 0 0\n", &HashMap::new()).unwrap_err();
+    assert_eq!(error.to_string(), "line 1: vertex position needs 3 to 7 numbers, found 2");
+
+    // Five values are neither a w nor a color.
+    let error = ObjLoader::parse("v 0 0 0\nv 0 0 0 1 1\n", &HashMap::new()).unwrap_err();
+    assert_eq!(error.to_string(), "line 2: vertex position needs 3, 4, 6 or 7 numbers, found 5");
+
+    for values in ["0 0 0", "0 0 0 1", "0 0 0 1 0 0", "0 0 0 1 1 0 0"].iter() {
+        assert!(ObjLoader::parse(&format!("v {}\n", values), &HashMap::new()).is_ok(), "{}", values);
+    }
+
+    match ObjLoader::parseMaterials("Kd 1 1 1\n") {
+        Err(LoadError::Parse { line: 1, .. }) => {},
+        other => panic!("expected a parse error, got {:?}", other)
+    }
+}
+
+#[test]
+fn obj_load_reads_material_libraries() {
+    let dir = scratchDir("obj");
+
+    fs::write(dir.join("tri.mtl"), "newmtl green\nKd 0 1 0\n").unwrap();
+    fs::write(dir.join("tri.obj"), "mtllib tri.mtl # library\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl green\nf 1 2 3\n").unwrap();
+    fs::write(dir.join("missing.obj"), "mtllib nowhere.mtl tri.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl red\nf 1 2 3\n").unwrap();
+
+    let model = ObjLoader::load(dir.join("tri.obj")).unwrap();
+    assert!(model.mesh.getVertices().iter().all(|v| v.color == Color::new(0, 255, 0)));
+
+    // A missing library is skipped, the rest still load.
+    let model = ObjLoader::load(dir.join("missing.obj")).unwrap();
+    assert_eq!(model.materials.keys().collect::<Vec<_>>(), vec!["green"]);
+    assert!(model.mesh.getVertices().iter().all(|v| v.color == Color::WHITE));
+
+    assert!(matches!(ObjLoader::load(dir.join("absent.obj")), Err(LoadError::Io(_))));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// A binary STL whose header starts with "solid", as some exporters write.
+fn binaryStl(facets: &[[[f32; 3]; 4]]) -> Vec<u8> {
+    let mut bytes = vec![b' '; 80];
+    bytes[..5].copy_from_slice(b"solid");
+    bytes.extend_from_slice(&(facets.len() as u32).to_le_bytes());
+
+    for facet in facets.iter() {
+        for vector in facet.iter() {
+            for value in vector.iter() {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        bytes.extend_from_slice(&[0, 0]);
+    }
+
+    bytes
+}
+
+#[test]
+fn stl_ascii_facets_carry_their_normal() {
+    let mesh = StlLoader::parse(b"solid part
+  facet normal 0 0 2
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex 1 0 0
+      vertex 1 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid part
+").unwrap();
+
+    assert_eq!(mesh.getVertices().len(), 6);
+    assert_eq!(mesh.getTriangle(1), Some([3, 4, 5]));
+    assert_eq!(mesh.getVertices()[4].position, Vector3::new(1.0, 1.0, 0.0));
+
+    for v in mesh.getVertices() {
+        assert_eq!(v.normal, Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(v.color, Color::WHITE);
+    }
+
+    // Whitespace may come before the "solid" keyword.
+    assert_eq!(StlLoader::parse(b"\n\t solid part\nendsolid part\n").unwrap().getTriangleCount(), 0);
+}
+
+#[test]
+fn stl_binary_is_detected_by_size() {
+    let mesh = StlLoader::parse(&binaryStl(&[
+        [[0.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]],
+        [[1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]]
+    ])).unwrap();
+
+    assert_eq!(mesh.getTriangleCount(), 2);
+    assert_eq!(mesh.getVertices()[1].position, Vector3::new(0.0, 1.0, 0.0));
+
+    // The first facet has no stored normal, so its winding decides.
+    assert_eq!(mesh.getVertices()[0].normal, Vector3::new(0.0, 0.0, -1.0));
+    assert_eq!(mesh.getVertices()[3].normal, Vector3::new(1.0, 0.0, 0.0));
+
+    // Neither a stored normal nor an area to take one from.
+    let degenerate = StlLoader::parse(&binaryStl(&[[[0.0; 3], [0.0; 3], [1.0, 1.0, 1.0], [2.0, 2.0, 2.0]]])).unwrap();
+    assert_eq!(degenerate.getVertices()[0].normal, Vector3::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn stl_reports_errors() {
+    let (line, message) = errorLine(StlLoader::parse(b"solid broken
+facet normal 0 0 1
+outer loop
+vertex 0 0 0
+vertex 1 0 0
+endloop
+endfacet
+"));
+    assert_eq!(line, 7);
+    assert!(message.contains("2 vertices"), "{}", message);
+
+    let (line, message) = errorLine(StlLoader::parse(b"solid broken\nfacet normal 0 0 1\nvertex 0 zero 0\n"));
+    assert_eq!(line, 3);
+    assert!(message.contains("'zero'"), "{}", message);
+
+    let mut truncated = binaryStl(&[[[0.0; 3]; 4]]);
+    truncated[0] = b'x';
+    truncated.pop();
+
+    match StlLoader::parse(&truncated) {
+        Err(LoadError::Format(_)) => {},
+        other => panic!("expected a format error, got {:?}", other)
+    }
+
+    for value in [f32::NAN, f32::INFINITY].iter() {
+        match StlLoader::parse(&binaryStl(&[[[0.0; 3], [0.0; 3], [*value, 1.0, 0.0], [1.0, 0.0, 0.0]]])) {
+            Err(LoadError::Format(message)) => assert!(message.contains("invalid number"), "{}", message),
+            other => panic!("expected a format error, got {:?}", other)
+        }
+    }
+}
+
+#[test]
+fn ply_ascii_reads_colors_and_fans_faces() {
+    let mesh = PlyLoader::parse(b"ply
+format ascii 1.0
+comment made by hand
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+").unwrap();
+
+    assert_eq!(mesh.getIndices(), &[0, 1, 2, 0, 2, 3]);
+    assert_eq!(mesh.getVertices()[2].position, Vector3::new(1.0, 1.0, 0.0));
+    assert_eq!(mesh.getVertices()[0].color, Color::new(255, 0, 0));
+    assert_eq!(mesh.getVertices()[2].color, Color::new(0, 0, 255));
+}
+
+#[test]
+fn ply_binary_little_endian() {
+    let mut bytes = b"ply
+format binary_little_endian 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+property float s
+property float t
+element material 1
+property list uchar float values
+element face 1
+property uchar flags
+property list uchar uint vertex_indices
+end_header
+".to_vec();
+
+    let vertices: [[f32; 8]; 3] = [
+        [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+        [2.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0],
+        [0.0, 2.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0]
+    ];
+
+    for v in vertices.iter() {
+        for value in v.iter() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    // A material element the loader has to skip over.
+    bytes.push(2);
+    bytes.extend_from_slice(&0.5f32.to_le_bytes());
+    bytes.extend_from_slice(&0.25f32.to_le_bytes());
+
+    bytes.push(7);
+    bytes.push(3);
+
+    for index in [0u32, 1, 2].iter() {
+        bytes.extend_from_slice(&index.to_le_bytes());
+    }
+
+    let mesh = PlyLoader::parse(&bytes).unwrap();
+
+    assert_eq!(mesh.getIndices(), &[0, 1, 2]);
+    assert_eq!(mesh.getVertices()[1].position, Vector3::new(2.0, 0.0, 0.0));
+    assert_eq!(mesh.getVertices()[1].normal, Vector3::new(0.0, 0.0, 1.0));
+    assert_eq!(mesh.getVertices()[2].uv, Vector2::new(0.0, 1.0));
+    assert_eq!(mesh.getVertices()[2].color, Color::WHITE);
+
+    bytes.pop();
+
+    match PlyLoader::parse(&bytes) {
+        Err(LoadError::Format(message)) => assert!(message.contains("end of data"), "{}", message),
+        other => panic!("expected a format error, got {:?}", other)
+    }
+}
+
+#[test]
+fn ply_reports_errors() {
+    let (line, message) = errorLine(PlyLoader::parse(b"ply
+format binary_big_endian 1.0
+end_header
+"));
+    assert_eq!(line, 2);
+    assert!(message.contains("binary_big_endian"), "{}", message);
+
+    let (line, message) = errorLine(PlyLoader::parse(b"ply
+format ascii 1.0
+element vertex 2
+property float x
+property float y
+end_header
+0 0
+1 one
+"));
+    assert_eq!(line, 8);
+    assert!(message.contains("'one'"), "{}", message);
+
+    match PlyLoader::parse(b"ply
+format ascii 1.0
+element vertex 1
+property float x
+element face 1
+property list uchar int vertex_indices
+end_header
+0
+3 0 1 2
+") {
+        Err(LoadError::Format(message)) => assert!(message.contains("vertex 1"), "{}", message),
+        other => panic!("expected a format error, got {:?}", other)
+    }
+}
+
+fn plyFormatError(bytes: &[u8]) -> String {
+    match PlyLoader::parse(bytes) {
+        Err(LoadError::Format(message)) => message,
+        other => panic!("expected a format error, got {:?}", other)
+    }
+}
+
+#[test]
+fn ply_rejects_bad_values() {
+    let triangle = "ply
+format ascii 1.0
+element vertex 3
+property float x
+element face 1
+property list uchar int vertex_indices
+end_header
+0
+1
+2
+3 0 1 2
+";
+
+    assert!(PlyLoader::parse(triangle.as_bytes()).is_ok());
+    assert!(PlyLoader::parse(format!("{}\n \n", triangle).as_bytes()).is_ok());
+
+    // Leftovers after the last element, on its line or after it.
+    let message = plyFormatError(triangle.replace("3 0 1 2", "3 0 1 2 3").as_bytes());
+    assert!(message.contains("line 11"), "{}", message);
+    plyFormatError(format!("{}4 0 1 2 0\n", triangle).as_bytes());
+
+    // Face indices must be whole numbers.
+    let message = plyFormatError(triangle.replace("uchar int", "uchar float").as_bytes());
+    assert!(message.contains("floating point"), "{}", message);
+    let message = plyFormatError(triangle.replace("3 0 1 2", "3 0 1.5 2").as_bytes());
+    assert!(message.contains("1.5"), "{}", message);
+
+    // Binary floats must be finite.
+    let mut bytes = b"ply
+format binary_little_endian 1.0
+element vertex 1
+property float x
+property double y
+end_header
+".to_vec();
+    let header = bytes.len();
+
+    bytes.extend_from_slice(&1.0f32.to_le_bytes());
+    bytes.extend_from_slice(&f64::NAN.to_le_bytes());
+    assert!(plyFormatError(&bytes).contains("'y'"));
+
+    bytes.truncate(header);
+    bytes.extend_from_slice(&f32::NEG_INFINITY.to_le_bytes());
+    bytes.extend_from_slice(&0.0f64.to_le_bytes());
+    assert!(plyFormatError(&bytes).contains("'x'"));
+}
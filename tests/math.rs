@@ -0,0 +1,189 @@
+#![allow(non_snake_case)]
+extern crate ncurses_rendering_engine;
+
+use ncurses_rendering_engine::quaternions::Quaternion::*;
+use ncurses_rendering_engine::matrices::Matrix44::*;
+use ncurses_rendering_engine::vectors::{VectorProperties::*, Vector3::*, Vector4::*};
+use ncurses_rendering_engine::math_utils::GeneralMath;
+
+fn assertNear(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+}
+
+fn assertVectorNear(actual: Vector3, expected: Vector3) {
+    assert!((actual - expected).x.abs() < 1e-9 && (actual - expected).y.abs() < 1e-9 && (actual - expected).z.abs() < 1e-9,
+        "{:?} != {:?}", actual, expected);
+}
+
+fn assertMatrixNear(actual: &Matrix44, expected: &Matrix44) {
+    let a = [actual.m00, actual.m01, actual.m02, actual.m03, actual.m10, actual.m11, actual.m12, actual.m13,
+        actual.m20, actual.m21, actual.m22, actual.m23, actual.m30, actual.m31, actual.m32, actual.m33];
+    let e = [expected.m00, expected.m01, expected.m02, expected.m03, expected.m10, expected.m11, expected.m12, expected.m13,
+        expected.m20, expected.m21, expected.m22, expected.m23, expected.m30, expected.m31, expected.m32, expected.m33];
+
+    assert!(a.iter().zip(e.iter()).all(|(a, e)| (a - e).abs() < 1e-9), "{:?} != {:?}", actual, expected);
+}
+
+// q and -q are the same rotation.
+fn assertSameRotation(actual: &Quaternion, expected: &Quaternion) {
+    assertNear(Quaternion::dot(actual, expected).abs(), 1.0);
+}
+
+fn transform(m: &Matrix44, v: &Vector3) -> Vector3 {
+    let p = *m * Vector4::new(v.x, v.y, v.z, 1.0);
+    Vector3::new(p.x, p.y, p.z)
+}
+
+fn rotation(axis: &Vector3, angle: f64) -> Matrix44 {
+    let mut m: Matrix44 = Default::default();
+    m.rotate(axis, angle);
+    m
+}
+
+#[test]
+fn axis_angle_matches_matrix_rotation() {
+    let axes = [Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, 0.0, 1.0), Vector3::new(1.0, -2.0, 0.5)];
+    let v = Vector3::new(0.3, -1.2, 2.0);
+
+    for axis in axes.iter() {
+        for degrees in [30.0, 90.0, -135.0].iter() {
+            let angle = GeneralMath::toRadians(*degrees);
+            // Matrix44::rotate wants a unit axis; fromAxisAngle normalizes.
+            let q = Quaternion::fromAxisAngle(axis, angle);
+            let m = rotation(&Vector3::getNormalized(axis), angle);
+
+            assertMatrixNear(&q.toMatrix(), &m);
+            assertVectorNear(q.rotate(&v), transform(&m, &v));
+            assertVectorNear(q * v, transform(&m, &v));
+            assertSameRotation(&Quaternion::fromMatrix(&m), &q);
+        }
+    }
+
+    // A quarter turn about z takes x to y.
+    let q = Quaternion::fromAxisAngle(&Vector3::new(0.0, 0.0, 2.0), GeneralMath::toRadians(90.0));
+    assertVectorNear(q.rotate(&Vector3::new(1.0, 0.0, 0.0)), Vector3::new(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn euler_angles_apply_roll_pitch_then_yaw() {
+    let (pitch, yaw, roll) = (GeneralMath::toRadians(20.0), GeneralMath::toRadians(-50.0), GeneralMath::toRadians(75.0));
+
+    let mut m = rotation(&Vector3::new(0.0, 1.0, 0.0), yaw);
+    m.rotate(&Vector3::new(1.0, 0.0, 0.0), pitch);
+    m.rotate(&Vector3::new(0.0, 0.0, 1.0), roll);
+
+    let q = Quaternion::fromEuler(pitch, yaw, roll);
+    assertMatrixNear(&q.toMatrix(), &m);
+    assertSameRotation(&Quaternion::fromMatrix(&q.toMatrix()), &q);
+}
+
+#[test]
+fn inverse_undoes_rotation() {
+    let q = Quaternion::fromEuler(0.4, 1.1, -0.7);
+    let v = Vector3::new(1.0, 2.0, 3.0);
+
+    assertVectorNear(q.inverse().rotate(&q.rotate(&v)), v);
+    assertSameRotation(&(q * q.inverse()), &Quaternion::default());
+
+    // Not unit length, the inverse still multiplies to the identity.
+    let scaled = Quaternion::new(q.x * 2.0, q.y * 2.0, q.z * 2.0, q.w * 2.0);
+    let identity = scaled * scaled.inverse();
+    assertNear(identity.w, 1.0);
+    assertNear(identity.x.abs() + identity.y.abs() + identity.z.abs(), 0.0);
+}
+
+#[test]
+fn slerp_hits_endpoints_and_midpoint() {
+    let axis = Vector3::new(0.0, 1.0, 0.0);
+    let a = Quaternion::fromAxisAngle(&axis, 0.0);
+    let b = Quaternion::fromAxisAngle(&axis, GeneralMath::toRadians(120.0));
+
+    assertSameRotation(&Quaternion::slerp(&a, &b, 0.0), &a);
+    assertSameRotation(&Quaternion::slerp(&a, &b, 1.0), &b);
+    assertSameRotation(&Quaternion::slerp(&a, &b, 0.5), &Quaternion::fromAxisAngle(&axis, GeneralMath::toRadians(60.0)));
+    assertSameRotation(&Quaternion::slerp(&a, &b, 0.25), &Quaternion::fromAxisAngle(&axis, GeneralMath::toRadians(30.0)));
+
+    // nlerp lands on the same midpoint, but not at constant speed.
+    assertSameRotation(&Quaternion::nlerp(&a, &b, 0.5), &Quaternion::fromAxisAngle(&axis, GeneralMath::toRadians(60.0)));
+    assertNear(Quaternion::nlerp(&a, &b, 0.25).length(), 1.0);
+    assert!(Quaternion::dot(&Quaternion::nlerp(&a, &b, 0.25), &Quaternion::fromAxisAngle(&axis, GeneralMath::toRadians(30.0))) < 1.0 - 1e-6);
+}
+
+#[test]
+fn interpolation_takes_the_shorter_path() {
+    let axis = Vector3::new(0.0, 0.0, 1.0);
+    let a = Quaternion::fromAxisAngle(&axis, GeneralMath::toRadians(10.0));
+    let b = Quaternion::fromAxisAngle(&axis, GeneralMath::toRadians(50.0));
+
+    let mut flipped = b;
+    flipped.negate();
+
+    // -b is the same rotation as b, so both go through 30 degrees rather
+    // than the long way round.
+    let expected = Quaternion::fromAxisAngle(&axis, GeneralMath::toRadians(30.0));
+
+    for end in [b, flipped].iter() {
+        assertSameRotation(&Quaternion::slerp(&a, end, 0.5), &expected);
+        assertSameRotation(&Quaternion::nlerp(&a, end, 0.5), &expected);
+    }
+}
+
+// Projects a point and divides by w.
+fn toNdc(m: &Matrix44, p: Vector3) -> Vector3 {
+    let clip = *m * Vector4::new(p.x, p.y, p.z, 1.0);
+    Vector3::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w)
+}
+
+#[test]
+fn perspective_maps_the_frustum_to_ndc() {
+    let m = Matrix44::perspective(GeneralMath::toRadians(90.0), 2.0, 1.0, 10.0);
+
+    // Near and far corners of the view volume land on the NDC cube's corners.
+    assertVectorNear(toNdc(&m, Vector3::new(0.0, 0.0, -1.0)), Vector3::new(0.0, 0.0, -1.0));
+    assertVectorNear(toNdc(&m, Vector3::new(2.0, 1.0, -1.0)), Vector3::new(1.0, 1.0, -1.0));
+    assertVectorNear(toNdc(&m, Vector3::new(-20.0, -10.0, -10.0)), Vector3::new(-1.0, -1.0, 1.0));
+
+    // Twice as far away, half as far from the center.
+    assertVectorNear(toNdc(&m, Vector3::new(2.0, 1.0, -2.0)), Vector3::new(0.5, 0.5, toNdc(&m, Vector3::new(0.0, 0.0, -2.0)).z));
+
+    // A symmetric frustum is the same projection, an off-center one shifts it.
+    let symmetric = Matrix44::frustum(-2.0, 2.0, -1.0, 1.0, 1.0, 10.0);
+    assertMatrixNear(&symmetric, &m);
+
+    let offCenter = Matrix44::frustum(0.0, 2.0, 0.0, 1.0, 1.0, 10.0);
+    assertVectorNear(toNdc(&offCenter, Vector3::new(0.0, 0.0, -1.0)), Vector3::new(-1.0, -1.0, -1.0));
+    assertVectorNear(toNdc(&offCenter, Vector3::new(20.0, 10.0, -10.0)), Vector3::new(1.0, 1.0, 1.0));
+
+    assertMatrixNear(&Matrix44::terminalPerspective(1.0, 80, 24, 0.5, 50.0),
+        &Matrix44::perspective(1.0, GeneralMath::terminalAspect(80, 24), 0.5, 50.0));
+}
+
+#[test]
+fn orthographic_maps_the_box_to_ndc() {
+    let m = Matrix44::orthographic(-2.0, 2.0, -1.0, 3.0, 1.0, 5.0);
+
+    assertVectorNear(toNdc(&m, Vector3::new(2.0, 3.0, -1.0)), Vector3::new(1.0, 1.0, -1.0));
+    assertVectorNear(toNdc(&m, Vector3::new(-2.0, -1.0, -5.0)), Vector3::new(-1.0, -1.0, 1.0));
+    assertVectorNear(toNdc(&m, Vector3::new(0.0, 1.0, -3.0)), Vector3::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn look_at_moves_the_eye_to_the_origin() {
+    let eye = Vector3::new(1.0, 2.0, 3.0);
+    let m = Matrix44::lookAt(&eye, &Vector3::new(1.0, 2.0, 0.0), &Vector3::new(0.0, 1.0, 0.0));
+
+    assertVectorNear(transform(&m, &eye), Vector3::new(0.0, 0.0, 0.0));
+    assertVectorNear(transform(&m, &Vector3::new(1.0, 2.0, 0.0)), Vector3::new(0.0, 0.0, -3.0));
+    assertVectorNear(transform(&m, &Vector3::new(2.0, 3.0, 3.0)), Vector3::new(1.0, 1.0, 0.0));
+
+    // Looking straight down the up axis still gives a rotation, with the old
+    // forward direction -z at the top of the view.
+    let down = Matrix44::lookAt(&Vector3::new(0.0, 5.0, 0.0), &Vector3::new(0.0, 0.0, 0.0), &Vector3::new(0.0, 1.0, 0.0));
+    assertVectorNear(transform(&down, &Vector3::new(0.0, 0.0, 0.0)), Vector3::new(0.0, 0.0, -5.0));
+    assertVectorNear(transform(&down, &Vector3::new(1.0, 0.0, 0.0)), Vector3::new(1.0, 0.0, -5.0));
+    assertVectorNear(transform(&down, &Vector3::new(0.0, 0.0, -1.0)), Vector3::new(0.0, 1.0, -5.0));
+
+    let up = Matrix44::lookAt(&Vector3::new(0.0, 0.0, 0.0), &Vector3::new(0.0, 0.0, 4.0), &Vector3::new(0.0, 0.0, 1.0));
+    assertVectorNear(transform(&up, &Vector3::new(0.0, 0.0, 4.0)), Vector3::new(0.0, 0.0, -4.0));
+    assertVectorNear(transform(&up, &Vector3::new(0.0, 1.0, 0.0)), Vector3::new(0.0, 1.0, 0.0));
+}
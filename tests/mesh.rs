@@ -0,0 +1,269 @@
+#![allow(non_snake_case)]
+extern crate ncurses_rendering_engine;
+
+mod snapshot;
+
+use std::cell::Cell;
+
+use ncurses_rendering_engine::rasterizer::{Rasterizer::*, CharGrid::*, Color::*, Fragment::*, Shader::*, FrameBuffer::*};
+use ncurses_rendering_engine::pipeline::{Pipeline::*, Clipper::*};
+use ncurses_rendering_engine::camera::Camera::*;
+use ncurses_rendering_engine::mesh::{Mesh::*, Primitives::*};
+use ncurses_rendering_engine::matrices::Matrix44::*;
+use ncurses_rendering_engine::vectors::{VectorProperties::*, Vector3::*, Vector4::*};
+use ncurses_rendering_engine::math_utils::GeneralMath;
+
+const WIDTH: i32 = 32;
+const HEIGHT: i32 = 16;
+
+fn present<V, F>(rast: &Rasterizer<V, F>) -> CharGrid {
+    let mut grid = CharGrid::new(WIDTH, HEIGHT);
+    rast.getFrameBuffer().present(&mut grid);
+    grid
+}
+
+fn cameraPipeline() -> Pipeline {
+    let mut camera = Camera::new(Vector3::new(0.0, 1.0, 3.0), Projection::Perspective {
+        fovY: GeneralMath::toRadians(60.0),
+        near: 0.5,
+        far: 100.0
+    }, GeneralMath::terminalAspect(WIDTH, HEIGHT));
+    camera.lookAt(&Vector3::new(0.0, 0.0, 0.0));
+
+    let mut pipeline = Pipeline::new(Viewport::new(0.0, 0.0, WIDTH as f64, HEIGHT as f64));
+    pipeline.setProjection(&camera.getProjectionMatrix());
+    pipeline.setView(&camera.getViewMatrix());
+    pipeline
+}
+
+// Square pyramid with its base on y = -1, wound counter-clockwise from outside.
+fn pyramid() -> Mesh {
+    let mut mesh = Mesh::default();
+
+    let apex = mesh.addVertex(Vertex::fromPosition(Vector3::new(0.0, 1.0, 0.0)));
+    let corners: Vec<u32> = [(-1.0, 1.0), (1.0, 1.0), (1.0, -1.0), (-1.0, -1.0)].iter()
+        .map(|(x, z)| mesh.addVertex(Vertex::fromPosition(Vector3::new(*x, -1.0, *z))))
+        .collect();
+
+    for k in 0..4 {
+        mesh.addTriangle(apex, corners[k], corners[(k + 1) % 4]);
+    }
+
+    mesh.addTriangle(corners[0], corners[3], corners[2]);
+    mesh.addTriangle(corners[0], corners[2], corners[1]);
+    mesh
+}
+
+// Counts how often it runs so tests can check the transform cache.
+struct CountingShader {
+    calls: Cell<usize>
+}
+
+impl VertexShader for CountingShader {
+    type Input = Vertex;
+    type Uniforms = BasicUniforms;
+
+    fn shade(&self, input: &Vertex, uniforms: &BasicUniforms) -> ClipVertex {
+        self.calls.set(self.calls.get() + 1);
+        VertexShader::shade(&VertexColorShader, input, uniforms)
+    }
+}
+
+#[test]
+fn triangles_come_from_index_buffer() {
+    let mesh = pyramid();
+
+    assert_eq!(mesh.getVertices().len(), 5);
+    assert_eq!(mesh.getTriangleCount(), 6);
+    assert_eq!(mesh.getTriangle(1), Some([0, 2, 3]));
+    assert_eq!(mesh.getTriangle(6), None);
+    assert_eq!(mesh.triangles().count(), 6);
+}
+
+#[test]
+fn mesh_pyramid() {
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    let mut pipeline = cameraPipeline();
+
+    let mut model: Matrix44 = Default::default();
+    model.rotate(&Vector3::new(0.0, 1.0, 0.0), GeneralMath::toRadians(30.0));
+    pipeline.setModel(&model);
+
+    // Wireframe shows that the hidden faces were culled.
+    rast.setCullMode(CullMode::Back);
+    rast.setPolygonMode(PolygonMode::Line);
+    rast.drawMesh(&pyramid(), &pipeline);
+
+    snapshot::assertSnapshot("mesh_pyramid", &present(&rast));
+}
+
+#[test]
+fn mesh_matches_separate_triangles() {
+    let pipeline = cameraPipeline();
+    let mesh = pyramid();
+
+    let mut expected = Rasterizer::new(WIDTH, HEIGHT);
+    let mut actual = Rasterizer::new(WIDTH, HEIGHT);
+
+    for [a, b, c] in mesh.triangles() {
+        let position = |i: u32| {
+            let p = mesh.getVertices()[i as usize].position;
+            Vector4::new(p.x, p.y, p.z, 1.0)
+        };
+
+        for [s1, s2, s3] in pipeline.processTriangle(&position(a), &position(b), &position(c)).iter() {
+            expected.rasterizeTriangle(s1, s2, s3);
+        }
+    }
+
+    actual.drawMesh(&mesh, &pipeline);
+
+    assert_eq!(present(&actual).to_string(), present(&expected).to_string());
+}
+
+#[test]
+fn vertex_colors_tint_base_color() {
+    let pipeline = cameraPipeline();
+    let mut mesh = pyramid();
+
+    for vertex in mesh.getVerticesMut().iter_mut() {
+        vertex.color = Color::new(255, 0, 0);
+    }
+
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    rast.setColor(Color::new(200, 100, 50));
+    rast.drawMesh(&mesh, &pipeline);
+
+    let cell = rast.getFrameBuffer().getCell(WIDTH / 2, HEIGHT / 2).unwrap();
+    assert_eq!(cell.color, Color::new(200, 0, 0));
+}
+
+#[test]
+fn shared_vertices_transform_once() {
+    let mesh = pyramid();
+    let pipeline = cameraPipeline();
+    let shader = CountingShader { calls: Cell::new(0) };
+
+    let mut rast = Rasterizer::withShaders(FrameBuffer::new(WIDTH, HEIGHT),
+        shader, VertexColorShader);
+    let uniforms = BasicUniforms::new(pipeline.getModelViewProjection(), Color::WHITE);
+
+    rast.drawIndexed(mesh.getVertices(), mesh.getIndices(), &uniforms);
+    assert_eq!(rast.getVertexShader().calls.get(), 5);
+
+    // Out of range triangles are skipped without touching their vertices.
+    rast.getVertexShaderMut().calls.set(0);
+    rast.drawIndexed(mesh.getVertices(), &[0, 1, 9, 0, 1, 2], &uniforms);
+    assert_eq!(rast.getVertexShader().calls.get(), 3);
+}
+
+#[test]
+fn fragments_see_interpolated_vertex_colors() {
+    struct Probe;
+
+    impl FragmentShader for Probe {
+        type Uniforms = BasicUniforms;
+
+        fn shade(&self, fragment: &Fragment, _uniforms: &BasicUniforms) -> Option<Shade> {
+            assert_eq!(fragment.varyings.len(), 3);
+            assert!(fragment.varyings.iter().all(|c| (0.0..=1.0).contains(c)));
+            None
+        }
+    }
+
+    let mesh = pyramid();
+    let pipeline = cameraPipeline();
+    let mut rast = Rasterizer::withShaders(FrameBuffer::new(WIDTH, HEIGHT),
+        VertexColorShader, Probe);
+
+    rast.drawIndexed(mesh.getVertices(), mesh.getIndices(), &BasicUniforms::new(pipeline.getModelViewProjection(), Color::WHITE));
+}
+
+fn primitives() -> Vec<(&'static str, Mesh)> {
+    vec![
+        ("cube", Primitives::cube(2.0, 2)),
+        ("plane", Primitives::plane(2.0, 1.0, 3, 2)),
+        ("uvSphere", Primitives::uvSphere(1.0, 8, 4)),
+        ("icosphere", Primitives::icosphere(1.0, 1)),
+        ("cylinder", Primitives::cylinder(1.0, 2.0, 8, 1)),
+        ("cone", Primitives::cone(1.0, 2.0, 8, 1)),
+        ("torus", Primitives::torus(1.0, 0.25, 8, 4)),
+        ("capsule", Primitives::capsule(1.0, 1.0, 8, 2))
+    ]
+}
+
+#[test]
+fn primitive_tessellation_counts() {
+    let counts: Vec<(usize, usize)> = primitives().iter()
+        .map(|(_, mesh)| (mesh.getVertices().len(), mesh.getTriangleCount()))
+        .collect();
+
+    // Triangles touching a pole or apex collapse and are left out, and the
+    // icosphere gains copies of the vertices on its texture seam.
+    assert_eq!(counts, vec![(54, 48), (12, 12), (45, 48), (49, 80), (38, 32), (28, 16), (45, 64), (54, 64)]);
+}
+
+#[test]
+fn primitives_wind_outward() {
+    for (name, mesh) in primitives() {
+        let v = mesh.getVertices();
+
+        for vertex in v.iter() {
+            assert!((Vector3::length(&vertex.normal) - 1.0).abs() < 1e-9, "{} has a non-unit normal", name);
+        }
+
+        for [a, b, c] in mesh.triangles() {
+            let (pa, pb, pc) = (v[a as usize].position, v[b as usize].position, v[c as usize].position);
+            let face = Vector3::cross(&(pb - pa), &(pc - pa));
+
+            for corner in [a, b, c].iter() {
+                assert!(Vector3::dot(&face, &v[*corner as usize].normal) > 0.0, "{} triangle {:?} faces away from its normals", name, [a, b, c]);
+            }
+
+            // Every closed shape but the torus is convex around the origin.
+            if name != "plane" && name != "torus" {
+                assert!(Vector3::dot(&face, &(pa + pb + pc)) > 0.0, "{} triangle {:?} is wound inward", name, [a, b, c]);
+            }
+        }
+    }
+}
+
+#[test]
+fn primitives_match_their_dimensions() {
+    for (name, mesh) in primitives() {
+        for vertex in mesh.getVertices() {
+            let p = vertex.position;
+            let ring = (p.x * p.x + p.z * p.z).sqrt();
+
+            let fits = match name {
+                "uvSphere" | "icosphere" => (Vector3::length(&p) - 1.0).abs() < 1e-9,
+                "cube" => p.x.abs().max(p.y.abs()).max(p.z.abs()) == 1.0,
+                "plane" => p.y == 0.0 && p.x.abs() <= 1.0 && p.z.abs() <= 0.5,
+                "cylinder" => p.y.abs() <= 1.0 && (ring == 0.0 || (ring - 1.0).abs() < 1e-9),
+                "cone" => (p.y == -1.0 && ring <= 1.0 + 1e-9) || (ring - (1.0 - p.y) * 0.5).abs() < 1e-9,
+                "torus" => (((ring - 1.0).powi(2) + p.y * p.y).sqrt() - 0.25).abs() < 1e-9,
+                _ => {
+                    let axis = Vector3::new(0.0, p.y.clamp(-0.5, 0.5), 0.0);
+                    (Vector3::dist(&p, &axis) - 1.0).abs() < 1e-9
+                }
+            };
+
+            assert!(fits, "{} vertex {:?} is off the surface", name, p);
+
+            let maxU = if name == "icosphere" { 1.5 } else { 1.0 };
+            assert!((0.0..=maxU).contains(&vertex.uv.x) && (0.0..=1.0).contains(&vertex.uv.y), "{} uv {:?}", name, vertex.uv);
+        }
+    }
+}
+
+#[test]
+fn primitive_torus() {
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    let pipeline = cameraPipeline();
+
+    rast.setCullMode(CullMode::Back);
+    rast.setPolygonMode(PolygonMode::Line);
+    rast.drawMesh(&Primitives::torus(1.0, 0.4, 12, 6), &pipeline);
+
+    snapshot::assertSnapshot("primitive_torus", &present(&rast));
+}
@@ -0,0 +1,93 @@
+#![allow(non_snake_case)]
+extern crate ncurses_rendering_engine;
+
+use ncurses_rendering_engine::pipeline::Clipper::*;
+use ncurses_rendering_engine::vectors::Vector4::*;
+
+fn vertex(x: f64, y: f64, z: f64, w: f64, varyings: &[f64]) -> ClipVertex {
+    ClipVertex::new(Vector4::new(x, y, z, w), varyings.to_vec())
+}
+
+#[test]
+fn triangle_crossing_near_plane_becomes_a_quad() {
+    // The first corner is behind the near plane, z < -w.
+    let behind = vertex(0.0, 0.0, -2.0, 1.0, &[0.0, 2.0]);
+    let a = vertex(0.5, 0.0, 0.0, 1.0, &[1.0, 0.0]);
+    let b = vertex(-0.5, 0.5, 0.0, 1.0, &[1.0, 4.0]);
+
+    // Both crossing edges are cut halfway, on the plane itself.
+    let cutA = vertex(0.25, 0.0, -1.0, 1.0, &[0.5, 1.0]);
+    let cutB = vertex(-0.25, 0.25, -1.0, 1.0, &[0.5, 3.0]);
+
+    assert_eq!(Clipper::clipAgainstPlane(&[behind.clone(), a.clone(), b.clone()], ClipPlane::Near),
+        vec![cutA.clone(), a.clone(), b.clone(), cutB.clone()]);
+
+    let triangles = Clipper::clipTriangle(&behind, &a, &b);
+    assert_eq!(triangles, vec![[cutA.clone(), a, b.clone()], [cutA, b, cutB]]);
+
+    for triangle in triangles.iter() {
+        assert!(triangle.iter().all(|v| Clipper::isInside(&v.position)));
+    }
+}
+
+#[test]
+fn triangles_outside_the_volume_are_dropped() {
+    let right = [
+        vertex(2.0, 0.0, 0.0, 1.0, &[]),
+        vertex(3.0, 1.0, 0.0, 1.0, &[]),
+        vertex(2.0, -1.0, 0.5, 1.0, &[])
+    ];
+
+    assert!(Clipper::clipTriangle(&right[0], &right[1], &right[2]).is_empty());
+    assert!(Clipper::clipPolygon(&right).is_empty());
+    assert_eq!(Clipper::clipLine(&right[0], &right[1]), None);
+
+    // No single plane rejects every corner, yet the triangle passes beyond
+    // the corner of the volume.
+    let around = [
+        vertex(0.0, 3.0, 0.0, 1.0, &[]),
+        vertex(3.0, 0.0, 0.0, 1.0, &[]),
+        vertex(3.0, 3.0, 0.0, 1.0, &[])
+    ];
+
+    assert!(Clipper::clipTriangle(&around[0], &around[1], &around[2]).is_empty());
+
+    // Entirely inside, a triangle comes back as it was.
+    let inside = [
+        vertex(0.0, 0.0, 0.0, 1.0, &[1.0]),
+        vertex(0.5, 0.0, 0.0, 1.0, &[2.0]),
+        vertex(0.0, 0.5, 0.0, 1.0, &[3.0])
+    ];
+
+    assert_eq!(Clipper::clipTriangle(&inside[0], &inside[1], &inside[2]), vec![inside.clone()]);
+}
+
+#[test]
+fn varyings_follow_the_clip_point() {
+    // w differs between the endpoints, and the cut lands where x = w.
+    let a = vertex(0.0, 0.0, 0.0, 1.0, &[0.0, 10.0]);
+    let b = vertex(3.0, 0.0, 0.0, 2.0, &[8.0, 0.0]);
+
+    let [start, end] = Clipper::clipLine(&a, &b).unwrap();
+    assert_eq!(start, a);
+    assert_eq!(end, vertex(1.5, 0.0, 0.0, 1.5, &[4.0, 5.0]));
+
+    // Cut at both ends, a quarter of the way in from each.
+    let c = vertex(-2.0, 0.0, 0.0, 1.0, &[0.0]);
+    let d = vertex(2.0, 0.0, 0.0, 1.0, &[4.0]);
+
+    let [start, end] = Clipper::clipLine(&c, &d).unwrap();
+    assert_eq!(start, vertex(-1.0, 0.0, 0.0, 1.0, &[1.0]));
+    assert_eq!(end, vertex(1.0, 0.0, 0.0, 1.0, &[3.0]));
+
+    // Across the far plane, every varying is cut at the same point.
+    let polygon = Clipper::clipAgainstPlane(&[
+        vertex(0.0, 0.0, 0.0, 1.0, &[0.0, 1.0, -1.0]),
+        vertex(0.0, 0.0, 3.0, 1.0, &[3.0, 1.0, 2.0]),
+        vertex(0.5, 0.0, 0.0, 1.0, &[0.0, 0.0, 0.0])
+    ], ClipPlane::Far);
+
+    assert_eq!(polygon.len(), 4);
+    assert_eq!(polygon[1], vertex(0.0, 0.0, 1.0, 1.0, &[1.0, 1.0, 0.0]));
+    assert!(polygon.iter().all(|v| ClipPlane::Far.distance(&v.position) >= 0.0));
+}
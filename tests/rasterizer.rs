@@ -0,0 +1,733 @@
+#![allow(non_snake_case)]
+extern crate ncurses_rendering_engine;
+
+mod snapshot;
+
+use ncurses_rendering_engine::rasterizer::{Rasterizer::*, CharGrid::*, GlyphRamp::*, FrameBuffer::*, Fragment::*, Color::*, Shader::*};
+use ncurses_rendering_engine::pipeline::Clipper::*;
+use ncurses_rendering_engine::matrices::Matrix44::*;
+use ncurses_rendering_engine::pipeline::Pipeline::*;
+use ncurses_rendering_engine::camera::Camera::*;
+use ncurses_rendering_engine::vectors::{Vector2::*, Vector3::*, Vector4::*};
+use ncurses_rendering_engine::math_utils::GeneralMath;
+
+const WIDTH: i32 = 32;
+const HEIGHT: i32 = 16;
+
+fn present(rast: &Rasterizer) -> CharGrid {
+    let mut grid = CharGrid::new(WIDTH, HEIGHT);
+    rast.getFrameBuffer().present(&mut grid);
+    grid
+}
+
+fn cameraPipeline(rast: &Rasterizer) -> Pipeline {
+    let mut camera = Camera::new(Vector3::new(0.0, 0.0, 3.0), Projection::Perspective {
+        fovY: GeneralMath::toRadians(60.0),
+        near: 0.5,
+        far: 100.0
+    }, GeneralMath::terminalAspect(WIDTH, HEIGHT));
+    camera.lookAt(&Vector3::new(0.0, 0.0, 0.0));
+
+    let mut pipeline = Pipeline::new(Viewport::fromFrameBuffer(rast.getFrameBuffer()));
+    pipeline.setProjection(&camera.getProjectionMatrix());
+    pipeline.setView(&camera.getViewMatrix());
+    pipeline
+}
+
+fn drawWorldTriangle(rast: &mut Rasterizer, pipeline: &Pipeline, v1: Vector4, v2: Vector4, v3: Vector4) {
+    for [s1, s2, s3] in pipeline.processTriangle(&v1, &v2, &v3).iter() {
+        rast.rasterizeTriangle(s1, s2, s3);
+    }
+}
+
+// Passes a per-vertex height through to the fragments.
+struct HeightVertexShader;
+
+struct StripeUniforms {
+    modelViewProjection: Matrix44,
+    bands: f64
+}
+
+impl VertexShader for HeightVertexShader {
+    type Input = (Vector4, f64);
+    type Uniforms = StripeUniforms;
+
+    fn shade(&self, input: &(Vector4, f64), uniforms: &StripeUniforms) -> ClipVertex {
+        ClipVertex::new(uniforms.modelViewProjection * input.0, vec![input.1])
+    }
+}
+
+// Draws alternate height bands and discards the rest.
+struct StripeFragmentShader;
+
+impl FragmentShader for StripeFragmentShader {
+    type Uniforms = StripeUniforms;
+
+    fn shade(&self, fragment: &Fragment, uniforms: &StripeUniforms) -> Option<Shade> {
+        if (fragment.varyings[0] * uniforms.bands).floor() as i32 % 2 == 1 {
+            return None;
+        }
+
+        Some(Shade::withGlyph('=', Color::WHITE))
+    }
+}
+
+fn renderScene(name: &str) -> CharGrid {
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+
+    match name {
+        "single_triangle" => {
+            rast.rasterizeTriangle(&Vector3::new(4.0, 2.0, 0.5),
+                &Vector3::new(26.0, 6.0, 0.5),
+                &Vector3::new(10.0, 13.0, 0.5));
+        },
+        "back_face_culled" => {
+            rast.setCullMode(CullMode::Back);
+
+            // Counter-clockwise on screen, kept.
+            rast.rasterizeTriangle(&Vector3::new(2.0, 2.0, 0.5),
+                &Vector3::new(2.0, 12.0, 0.5),
+                &Vector3::new(13.0, 12.0, 0.5));
+
+            // Clockwise on screen, culled.
+            rast.rasterizeTriangle(&Vector3::new(18.0, 2.0, 0.5),
+                &Vector3::new(29.0, 12.0, 0.5),
+                &Vector3::new(18.0, 12.0, 0.5));
+        },
+        "perspective_triangle" => {
+            let pipeline = cameraPipeline(&rast);
+
+            drawWorldTriangle(&mut rast, &pipeline,
+                Vector4::new(-1.0, -1.0, 0.0, 1.0),
+                Vector4::new(1.0, -1.0, 0.0, 1.0),
+                Vector4::new(0.0, 1.0, 0.0, 1.0));
+        },
+        "near_plane_clipped" => {
+            let pipeline = cameraPipeline(&rast);
+
+            // Runs from in front of the camera to behind it.
+            drawWorldTriangle(&mut rast, &pipeline,
+                Vector4::new(-1.0, -0.5, -4.0, 1.0),
+                Vector4::new(1.0, -0.5, -4.0, 1.0),
+                Vector4::new(0.0, -0.5, 6.0, 1.0));
+        },
+        "shaded_triangle" => {
+            rast.rasterizeShadedTriangle(&Vector3::new(2.0, 1.0, 0.5),
+                &Vector3::new(29.0, 1.0, 0.5),
+                &Vector3::new(2.0, 14.0, 0.5), [0.1, 1.0, 0.5]);
+        },
+        "overlap_keeps_background" => {
+            rast.rasterizeShadedTriangle(&Vector3::new(2.0, 2.0, 0.5),
+                &Vector3::new(2.0, 14.0, 0.5),
+                &Vector3::new(14.0, 14.0, 0.5), [0.4, 0.4, 0.4]);
+
+            // Its bounding box covers the first triangle, which must survive.
+            rast.rasterizeTriangle(&Vector3::new(0.0, 0.0, 0.5),
+                &Vector3::new(30.0, 15.0, 0.5),
+                &Vector3::new(30.0, 0.0, 0.5));
+        },
+        "half_block_triangle" => {
+            rast = Rasterizer::withFrameBuffer(FrameBuffer::forTerminal(WIDTH, HEIGHT, PresentMode::HalfBlock));
+
+            rast.rasterizeTriangle(&Vector3::new(4.0, 3.0, 0.5),
+                &Vector3::new(12.0, 29.0, 0.5),
+                &Vector3::new(28.0, 9.0, 0.5));
+        },
+        "braille_triangle" => {
+            rast = Rasterizer::withFrameBuffer(FrameBuffer::forTerminal(WIDTH, HEIGHT, PresentMode::Braille));
+
+            rast.rasterizeTriangle(&Vector3::new(6.0, 4.0, 0.5),
+                &Vector3::new(20.0, 60.0, 0.5),
+                &Vector3::new(58.0, 20.0, 0.5));
+        },
+        "supersampled_sliver" => {
+            rast.setSampleMode(SampleMode::Grid(4));
+
+            // Thinner than a cell for most of its length.
+            rast.rasterizeTriangle(&Vector3::new(1.0, 2.0, 0.5),
+                &Vector3::new(30.0, 12.0, 0.5),
+                &Vector3::new(1.0, 3.0, 0.5));
+        },
+        "rotated_grid_triangle" => {
+            rast.setSampleMode(SampleMode::RotatedGrid);
+
+            rast.rasterizeTriangle(&Vector3::new(4.0, 2.0, 0.5),
+                &Vector3::new(26.0, 6.0, 0.5),
+                &Vector3::new(10.0, 13.0, 0.5));
+        },
+        "perspective_checkerboard" => {
+            let pipeline = cameraPipeline(&rast);
+
+            // A floor receding from the camera with UVs in the varyings. The
+            // squares must shrink with distance rather than stay uniform.
+            let corners = [
+                (Vector4::new(-2.0, -0.8, 2.0, 1.0), [0.0, 0.0]),
+                (Vector4::new(2.0, -0.8, 2.0, 1.0), [4.0, 0.0]),
+                (Vector4::new(2.0, -0.8, -12.0, 1.0), [4.0, 8.0]),
+                (Vector4::new(-2.0, -0.8, -12.0, 1.0), [0.0, 8.0])
+            ];
+
+            for [a, b, c] in [[0, 1, 2], [0, 2, 3]].iter() {
+                let (v1, v2, v3) = (&corners[*a], &corners[*b], &corners[*c]);
+
+                for [s1, s2, s3] in pipeline.processVaryingTriangle(&v1.0, &v2.0, &v3.0, [&v1.1, &v2.1, &v3.1]).iter() {
+                    rast.rasterizeVaryingTriangle(s1, s2, s3, |fragment| {
+                        let (u, v) = (fragment.varyings[0].floor() as i32, fragment.varyings[1].floor() as i32);
+                        let glyph = if (u + v) % 2 == 0 { '#' } else { '.' };
+                        Some(Shade::withGlyph(glyph, Color::WHITE))
+                    });
+                }
+            }
+        },
+        "shader_stripes" => {
+            let mut shaded = Rasterizer::withShaders(FrameBuffer::new(WIDTH, HEIGHT), HeightVertexShader, StripeFragmentShader);
+            let pipeline = cameraPipeline(&rast);
+
+            let uniforms = StripeUniforms {
+                modelViewProjection: *pipeline.getModelViewProjection(),
+                bands: 6.0
+            };
+
+            shaded.drawTriangle([
+                &(Vector4::new(-1.5, -1.0, 0.0, 1.0), 0.0),
+                &(Vector4::new(1.5, -1.0, 0.0, 1.0), 0.0),
+                &(Vector4::new(0.0, 1.2, 0.0, 1.0), 1.0)
+            ], &uniforms);
+
+            let mut grid = CharGrid::new(WIDTH, HEIGHT);
+            shaded.getFrameBuffer().present(&mut grid);
+            return grid;
+        },
+        "line_star" => {
+            let center = Vector3::new(16.5, 8.5, 0.5);
+            rast.rasterizePoint(&center);
+
+            for k in 0..8 {
+                let angle = k as f64 * std::f64::consts::PI / 4.0;
+                let end = Vector3::new(center.x + 14.0 * angle.cos(), center.y + 7.0 * angle.sin(), 0.5);
+                rast.rasterizeLine(&center, &end);
+            }
+        },
+        "wireframe_triangle" => {
+            rast.setPolygonMode(PolygonMode::Line);
+
+            rast.rasterizeTriangle(&Vector3::new(4.0, 2.0, 0.5),
+                &Vector3::new(26.0, 6.0, 0.5),
+                &Vector3::new(10.0, 13.0, 0.5));
+        },
+        "wireframe_clipped" => {
+            let pipeline = cameraPipeline(&rast);
+            rast.setPolygonMode(PolygonMode::Line);
+
+            // Crosses the near plane; only the original edges may show.
+            rast.drawTriangle([
+                &Vector4::new(-1.0, -0.5, -4.0, 1.0),
+                &Vector4::new(1.0, -0.5, -4.0, 1.0),
+                &Vector4::new(0.0, -0.5, 6.0, 1.0)
+            ], &BasicUniforms::new(pipeline.getModelViewProjection(), Color::WHITE));
+        },
+        _ => panic!("unknown scene '{}'", name)
+    }
+
+    present(&rast)
+}
+
+fn checkScene(name: &str) {
+    snapshot::assertSnapshot(name, &renderScene(name));
+}
+
+#[test]
+fn single_triangle() {
+    checkScene("single_triangle");
+}
+
+#[test]
+fn back_face_culled() {
+    checkScene("back_face_culled");
+}
+
+#[test]
+fn cull_mode_and_front_face_pick_the_kept_winding() {
+    let counterClockwise = [Vector3::new(2.0, 2.0, 0.5), Vector3::new(2.0, 12.0, 0.5), Vector3::new(13.0, 12.0, 0.5)];
+    let clockwise = [counterClockwise[0], counterClockwise[2], counterClockwise[1]];
+
+    // (front face, cull mode, counter-clockwise kept, clockwise kept)
+    let cases = [
+        (FrontFace::CounterClockwise, CullMode::None, true, true),
+        (FrontFace::CounterClockwise, CullMode::Back, true, false),
+        (FrontFace::CounterClockwise, CullMode::Front, false, true),
+        (FrontFace::Clockwise, CullMode::None, true, true),
+        (FrontFace::Clockwise, CullMode::Back, false, true),
+        (FrontFace::Clockwise, CullMode::Front, true, false)
+    ];
+
+    for (frontFace, cullMode, keepCounterClockwise, keepClockwise) in cases.iter() {
+        for (triangle, kept) in [(counterClockwise, keepCounterClockwise), (clockwise, keepClockwise)].iter() {
+            let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+            rast.setFrontFace(*frontFace);
+            rast.setCullMode(*cullMode);
+            rast.rasterizeTriangle(&triangle[0], &triangle[1], &triangle[2]);
+
+            let drawn = rast.getFrameBuffer().getCell(4, 10).unwrap().glyph != ' ';
+            assert_eq!(drawn, **kept, "{:?} {:?} {:?}", frontFace, cullMode, triangle);
+        }
+    }
+}
+
+#[test]
+fn perspective_triangle() {
+    checkScene("perspective_triangle");
+}
+
+#[test]
+fn near_plane_clipped() {
+    checkScene("near_plane_clipped");
+}
+
+#[test]
+fn shaded_triangle() {
+    checkScene("shaded_triangle");
+}
+
+#[test]
+fn overlap_keeps_background() {
+    checkScene("overlap_keeps_background");
+}
+
+#[test]
+fn half_block_triangle() {
+    checkScene("half_block_triangle");
+}
+
+#[test]
+fn braille_triangle() {
+    checkScene("braille_triangle");
+}
+
+#[test]
+fn supersampled_sliver() {
+    checkScene("supersampled_sliver");
+}
+
+#[test]
+fn rotated_grid_triangle() {
+    checkScene("rotated_grid_triangle");
+}
+
+#[test]
+fn perspective_checkerboard() {
+    checkScene("perspective_checkerboard");
+}
+
+#[test]
+fn shader_stripes() {
+    checkScene("shader_stripes");
+}
+
+#[test]
+fn line_star() {
+    checkScene("line_star");
+}
+
+#[test]
+fn wireframe_triangle() {
+    checkScene("wireframe_triangle");
+}
+
+#[test]
+fn wireframe_clipped() {
+    checkScene("wireframe_clipped");
+}
+
+#[test]
+fn slope_glyphs_follow_direction() {
+    assert_eq!(Rasterizer::slopeGlyph(1.0, 0.0, 1.0), '-');
+    assert_eq!(Rasterizer::slopeGlyph(-1.0, 0.1, 1.0), '-');
+    assert_eq!(Rasterizer::slopeGlyph(0.0, 1.0, 1.0), '|');
+    assert_eq!(Rasterizer::slopeGlyph(1.0, 1.0, 1.0), '\\');
+    assert_eq!(Rasterizer::slopeGlyph(1.0, -1.0, 1.0), '/');
+    assert_eq!(Rasterizer::slopeGlyph(-1.0, 1.0, 1.0), '/');
+
+    // Three cells across and one down looks diagonal on tall cells.
+    assert_eq!(Rasterizer::slopeGlyph(3.0, 1.0, 2.0), '\\');
+    assert_eq!(Rasterizer::slopeGlyph(3.0, 1.0, 1.0), '-');
+}
+
+#[test]
+fn line_interpolates_between_endpoints() {
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    let mut fragments: Vec<Fragment> = Vec::new();
+
+    // Starts off screen to the left; clipping must not shift the varyings.
+    let v1 = ScreenVertex::fromScreen(&Vector3::new(-10.5, 2.5, 0.0), vec![0.0]);
+    let v2 = ScreenVertex::fromScreen(&Vector3::new(29.5, 12.5, 1.0), vec![40.0]);
+
+    rast.rasterizeVaryingLine(&v1, &v2, |fragment| {
+        fragments.push(fragment.clone());
+        None
+    });
+
+    assert_eq!(fragments.first().map(|f| (f.x, f.y)), Some((0, 5)));
+    assert_eq!(fragments.last().map(|f| (f.x, f.y)), Some((29, 12)));
+
+    for pair in fragments.windows(2) {
+        assert_eq!(pair[1].x - pair[0].x, 1);
+        assert!(pair[1].y - pair[0].y <= 1);
+    }
+
+    for f in fragments.iter() {
+        assert!((f.varyings[0] - (f.x as f64 + 11.0)).abs() < 0.5);
+        assert!((f.depth - f.varyings[0] / 40.0).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn lines_on_the_far_edges_are_outside() {
+    let w = WIDTH as f64;
+    let h = HEIGHT as f64;
+
+    let drawn = |v1: Vector3, v2: Vector3| -> Vec<(i32, i32)> {
+        let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+        let mut cells: Vec<(i32, i32)> = Vec::new();
+
+        rast.rasterizeVaryingLine(&ScreenVertex::fromScreen(&v1, Vec::new()), &ScreenVertex::fromScreen(&v2, Vec::new()), |fragment| {
+            cells.push((fragment.x, fragment.y));
+            None
+        });
+
+        cells
+    };
+
+    // Running along x == width or y == height, or just touching them.
+    assert!(drawn(Vector3::new(w, 0.0, 0.5), Vector3::new(w, h - 1.0, 0.5)).is_empty());
+    assert!(drawn(Vector3::new(0.0, h, 0.5), Vector3::new(w - 1.0, h, 0.5)).is_empty());
+    assert!(drawn(Vector3::new(w, 3.0, 0.5), Vector3::new(w + 8.0, 3.0, 0.5)).is_empty());
+    assert!(drawn(Vector3::new(w + 2.0, h - 2.0, 0.5), Vector3::new(w - 2.0, h + 2.0, 0.5)).is_empty());
+
+    // The near edges are inside, as is the last row and column.
+    assert_eq!(drawn(Vector3::new(0.0, 0.0, 0.5), Vector3::new(0.0, 2.5, 0.5)), vec![(0, 0), (0, 1), (0, 2)]);
+    assert_eq!(drawn(Vector3::new(w - 0.5, h - 0.5, 0.5), Vector3::new(w - 0.5, h - 0.5, 0.5)), vec![(WIDTH - 1, HEIGHT - 1)]);
+
+    // Lines leaving through the far edge stop at the last column.
+    assert_eq!(drawn(Vector3::new(w - 2.5, 1.5, 0.5), Vector3::new(w + 5.0, 1.5, 0.5)), vec![(WIDTH - 3, 1), (WIDTH - 2, 1), (WIDTH - 1, 1)]);
+}
+
+#[test]
+fn points_outside_view_are_clipped() {
+    let rast = Rasterizer::new(WIDTH, HEIGHT);
+    let pipeline = cameraPipeline(&rast);
+
+    assert!(pipeline.processPoint(&Vector4::new(0.0, 0.0, 0.0, 1.0)).is_some());
+    assert!(pipeline.processPoint(&Vector4::new(0.0, 0.0, 5.0, 1.0)).is_none());
+    assert!(pipeline.processLine(&Vector4::new(0.0, 0.0, 5.0, 1.0), &Vector4::new(0.0, 1.0, 5.0, 1.0)).is_none());
+}
+
+#[test]
+fn basic_shaders_match_pipeline() {
+    let mut expected = Rasterizer::new(WIDTH, HEIGHT);
+    let mut actual = Rasterizer::new(WIDTH, HEIGHT);
+    let pipeline = cameraPipeline(&expected);
+
+    let v1 = Vector4::new(-1.0, -0.5, -4.0, 1.0);
+    let v2 = Vector4::new(1.0, -0.5, -4.0, 1.0);
+    let v3 = Vector4::new(0.0, -0.5, 6.0, 1.0);
+
+    drawWorldTriangle(&mut expected, &pipeline, v1, v2, v3);
+    actual.drawTriangle([&v1, &v2, &v3], &BasicUniforms::new(pipeline.getModelViewProjection(), Color::WHITE));
+
+    assert_eq!(present(&actual).to_string(), present(&expected).to_string());
+}
+
+#[test]
+fn varyings_are_perspective_correct() {
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    let pipeline = cameraPipeline(&rast);
+
+    let v1 = Vector4::new(-2.0, -1.0, 1.0, 1.0);
+    let v2 = Vector4::new(2.0, -1.0, 1.0, 1.0);
+    let v3 = Vector4::new(0.0, -1.0, -20.0, 1.0);
+
+    let mut fragments: Vec<Fragment> = Vec::new();
+
+    // World positions as varyings; each fragment's interpolated position
+    // must project back onto the center of its own cell.
+    for [s1, s2, s3] in pipeline.processVaryingTriangle(&v1, &v2, &v3,
+        [&[v1.x, v1.y, v1.z], &[v2.x, v2.y, v2.z], &[v3.x, v3.y, v3.z]]).iter() {
+        rast.rasterizeVaryingTriangle(s1, s2, s3, |fragment| {
+            fragments.push(fragment.clone());
+            None
+        });
+    }
+
+    assert!(fragments.len() > 20);
+
+    for f in fragments.iter() {
+        let p = pipeline.processVertex(&Vector4::new(f.varyings[0], f.varyings[1], f.varyings[2], 1.0));
+
+        assert!((p.x - (f.x as f64 + 0.5)).abs() < 0.05, "x at ({}, {}) was {}", f.x, f.y, p.x);
+        assert!((p.y - (f.y as f64 + 0.5)).abs() < 0.05, "y at ({}, {}) was {}", f.x, f.y, p.y);
+        assert!((p.z - f.depth).abs() < 1e-3);
+    }
+
+    // Every fragment was discarded.
+    assert!((0..HEIGHT).all(|y| (0..WIDTH).all(|x| rast.getFrameBuffer().getCell(x, y).unwrap().glyph == ' ')));
+}
+
+#[test]
+fn grid_offsets_stay_within_cell() {
+    assert_eq!(SampleMode::Grid(2).getOffsets(), vec![(-0.25, -0.25), (0.25, -0.25), (-0.25, 0.25), (0.25, 0.25)]);
+    assert_eq!(SampleMode::Grid(0).getOffsets(), SampleMode::Single.getOffsets());
+}
+
+#[test]
+fn glyph_ramp_maps_intensity() {
+    let ramp = GlyphRamp::new(" .:#");
+
+    assert_eq!(ramp.glyphFor(0.0), ' ');
+    assert_eq!(ramp.glyphFor(0.4), '.');
+    assert_eq!(ramp.glyphFor(1.0), '#');
+    assert_eq!(ramp.glyphFor(7.0), '#');
+    assert_eq!(ramp.glyphFor(-1.0), ' ');
+}
+
+#[test]
+fn point_in_triangle() {
+    let v1 = Vector2::new(0.0, 0.0);
+    let v2 = Vector2::new(10.0, 0.0);
+    let v3 = Vector2::new(0.0, 10.0);
+
+    assert!(Rasterizer::isPointInTriangle(2, 2, &v1, &v2, &v3));
+    assert!(Rasterizer::isPointInTriangle(2, 2, &v1, &v3, &v2));
+    assert!(!Rasterizer::isPointInTriangle(8, 8, &v1, &v2, &v3));
+    assert!(!Rasterizer::isPointInTriangle(-1, 3, &v1, &v2, &v3));
+
+    // Far beyond the guard band nothing is covered, and nothing overflows.
+    let far = Vector2::new(1e12, 0.0);
+    assert!(!Rasterizer::isPointInTriangle(2, 2, &v1, &far, &v3));
+    assert!(!Rasterizer::isPointInTriangle(i32::MAX, 0, &v1, &v2, &v3));
+}
+
+#[test]
+fn triangles_beyond_the_guard_band_are_rejected() {
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    rast.rasterizeTriangle(&Vector3::new(-1e9, -1e9, 0.5), &Vector3::new(1e9, -1e9, 0.5), &Vector3::new(0.0, 1e9, 0.5));
+    rast.rasterizeTriangle(&Vector3::new(0.0, 0.0, 0.5), &Vector3::new(f64::MAX, 0.0, 0.5), &Vector3::new(0.0, 4.0, 0.5));
+
+    assert_eq!(present(&rast), CharGrid::new(WIDTH, HEIGHT));
+
+    // Within it, large triangles still cover the screen.
+    rast.rasterizeTriangle(&Vector3::new(-1e5, -1e5, 0.5), &Vector3::new(1e5, -1e5, 0.5), &Vector3::new(0.0, 1e5, 0.5));
+    assert!(rast.getFrameBuffer().getCell(WIDTH - 1, HEIGHT - 1).unwrap().glyph != ' ');
+}
+
+// Counts, per cell, how many of the triangles cover it when each is drawn
+// into its own buffer.
+fn coverageCounts(triangles: &[[Vector3; 3]]) -> Vec<u32> {
+    let mut counts = vec![0; (WIDTH * HEIGHT) as usize];
+
+    for t in triangles.iter() {
+        let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+        rast.rasterizeTriangle(&t[0], &t[1], &t[2]);
+
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                if rast.getFrameBuffer().getCell(x, y).unwrap().glyph != ' ' {
+                    counts[(y * WIDTH + x) as usize] += 1;
+                }
+            }
+        }
+    }
+
+    counts
+}
+
+#[test]
+fn shared_edges_cover_cells_once() {
+    // A quad split along its diagonal, with corners on cell boundaries.
+    let a = Vector3::new(2.0, 1.0, 0.5);
+    let b = Vector3::new(14.0, 1.0, 0.5);
+    let c = Vector3::new(14.0, 13.0, 0.5);
+    let d = Vector3::new(2.0, 13.0, 0.5);
+
+    let counts = coverageCounts(&[[a, b, c], [a, c, d]]);
+
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let inside = (2..14).contains(&x) && (1..13).contains(&y);
+            assert_eq!(counts[(y * WIDTH + x) as usize], inside as u32, "cell ({}, {})", x, y);
+        }
+    }
+
+    // A fan with mixed windings around an off-grid center; no cell may be
+    // claimed twice.
+    let center = Vector3::new(23.3, 7.6, 0.5);
+    let rim: Vec<Vector3> = (0..7).map(|k| {
+        let angle = k as f64 * std::f64::consts::PI * 2.0 / 7.0;
+        Vector3::new(center.x + 7.5 * angle.cos(), center.y + 6.5 * angle.sin(), 0.5)
+    }).collect();
+
+    let fan: Vec<[Vector3; 3]> = (0..7).map(|k| {
+        let (p, q) = (rim[k], rim[(k + 1) % 7]);
+        if k % 2 == 0 { [center, p, q] } else { [q, p, center] }
+    }).collect();
+
+    let counts = coverageCounts(&fan);
+    assert!(counts.iter().all(|&n| n <= 1));
+    assert_eq!(counts[(7 * WIDTH + 23) as usize], 1);
+}
+
+#[test]
+fn nearer_triangle_wins_depth_test() {
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+
+    rast.rasterizeTriangle(&Vector3::new(0.0, 0.0, 0.2),
+        &Vector3::new(20.0, 0.0, 0.2),
+        &Vector3::new(0.0, 15.0, 0.2));
+    rast.rasterizeTriangle(&Vector3::new(0.0, 0.0, 0.8),
+        &Vector3::new(20.0, 0.0, 0.8),
+        &Vector3::new(0.0, 15.0, 0.8));
+
+    assert_eq!(rast.getFrameBuffer().getCell(2, 2).unwrap().depth, 0.2);
+
+    rast.setDepthTest(DepthTest::Always);
+    rast.setDepthWrite(false);
+    rast.rasterizeTriangle(&Vector3::new(0.0, 0.0, 0.8),
+        &Vector3::new(20.0, 0.0, 0.8),
+        &Vector3::new(0.0, 15.0, 0.8));
+
+    assert_eq!(rast.getFrameBuffer().getCell(2, 2).unwrap().depth, 0.2);
+}
+
+// Covers the top left of the screen at a constant depth.
+fn drawFlat(rast: &mut Rasterizer, depth: f64, color: Color) {
+    rast.setColor(color);
+    rast.rasterizeTriangle(&Vector3::new(0.0, 0.0, depth),
+        &Vector3::new(20.0, 0.0, depth),
+        &Vector3::new(0.0, 15.0, depth));
+}
+
+#[test]
+fn depth_tests_compare_against_the_stored_depth() {
+    let red = Color::new(255, 0, 0);
+    let blue = Color::new(0, 0, 255);
+
+    // (test, incoming depth, whether it passes over a stored 0.5)
+    let cases = [
+        (DepthTest::Less, 0.3, true),
+        (DepthTest::Less, 0.5, false),
+        (DepthTest::Less, 0.7, false),
+        (DepthTest::LessEqual, 0.3, true),
+        (DepthTest::LessEqual, 0.5, true),
+        (DepthTest::LessEqual, 0.7, false),
+        (DepthTest::Always, 0.7, true),
+        (DepthTest::Never, 0.3, false),
+        (DepthTest::Never, 0.5, false)
+    ];
+
+    for (test, depth, passes) in cases.iter() {
+        let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+        drawFlat(&mut rast, 0.5, red);
+
+        rast.setDepthTest(*test);
+        drawFlat(&mut rast, *depth, blue);
+
+        let cell = rast.getFrameBuffer().getCell(2, 2).unwrap();
+        let (color, stored) = if *passes { (blue, *depth) } else { (red, 0.5) };
+        assert_eq!(cell.color, color, "{:?} at {}", test, depth);
+        assert!((cell.depth - stored).abs() < 1e-9, "{:?} at {} stored {}", test, depth, cell.depth);
+    }
+}
+
+#[test]
+fn depth_write_can_be_turned_off() {
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    drawFlat(&mut rast, 0.5, Color::new(255, 0, 0));
+
+    // The color lands but the stored depth stays put.
+    rast.setDepthWrite(false);
+    drawFlat(&mut rast, 0.3, Color::new(0, 0, 255));
+
+    let cell = rast.getFrameBuffer().getCell(2, 2).unwrap();
+    assert_eq!((cell.color, cell.depth), (Color::new(0, 0, 255), 0.5));
+
+    // So something behind the last draw but in front of 0.5 still passes.
+    drawFlat(&mut rast, 0.4, Color::new(0, 255, 0));
+    assert_eq!(rast.getFrameBuffer().getCell(2, 2).unwrap().color, Color::new(0, 255, 0));
+
+    // With writes back on, depth follows the draw again.
+    rast.setDepthWrite(true);
+    drawFlat(&mut rast, 0.2, Color::WHITE);
+    assert_eq!(rast.getFrameBuffer().getCell(2, 2).unwrap().depth, 0.2);
+}
+
+#[test]
+fn supersampled_edges_keep_per_sample_depth() {
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    rast.setSampleMode(SampleMode::Grid(4));
+
+    let a = Vector3::new(2.0, 1.0, 0.5);
+    let c = Vector3::new(14.0, 13.0, 0.5);
+
+    rast.setColor(Color::new(255, 0, 0));
+    rast.rasterizeTriangle(&a, &Vector3::new(14.0, 1.0, 0.5), &c);
+
+    // The diagonal splits cell (5, 4); its depth is that of its nearest
+    // sample.
+    assert!((rast.getFrameBuffer().getCell(5, 4).unwrap().depth - 0.5).abs() < 1e-9);
+
+    // The other half, at the same depth, still covers its own samples and
+    // blends into the shared cells.
+    rast.setColor(Color::new(0, 0, 255));
+    rast.rasterizeTriangle(&a, &c, &Vector3::new(2.0, 13.0, 0.5));
+
+    let blended = rast.getFrameBuffer().getCell(5, 4).unwrap().color;
+    assert!(blended.r > 0 && blended.b > 0, "{:?}", blended);
+}
+
+#[test]
+fn far_geometry_does_not_paint_over_near_edges() {
+    let mut rast = Rasterizer::new(WIDTH, HEIGHT);
+    rast.setSampleMode(SampleMode::Grid(4));
+
+    rast.setColor(Color::new(255, 0, 0));
+    rast.rasterizeTriangle(&Vector3::new(3.0, 1.3, 0.2), &Vector3::new(20.6, 2.7, 0.2), &Vector3::new(6.4, 13.1, 0.2));
+    let near: Vec<Cell> = (0..WIDTH * HEIGHT).map(|i| *rast.getFrameBuffer().getCell(i % WIDTH, i / WIDTH).unwrap()).collect();
+
+    // A far quad behind the whole screen, drawn afterwards.
+    rast.setColor(Color::new(0, 0, 255));
+    rast.rasterizeTriangle(&Vector3::new(-1.0, -1.0, 0.8), &Vector3::new(40.0, -1.0, 0.8), &Vector3::new(40.0, 20.0, 0.8));
+    rast.rasterizeTriangle(&Vector3::new(-1.0, -1.0, 0.8), &Vector3::new(40.0, 20.0, 0.8), &Vector3::new(-1.0, 20.0, 0.8));
+
+    let mut edges = 0;
+
+    for (i, before) in near.iter().enumerate() {
+        let after = rast.getFrameBuffer().getCell(i as i32 % WIDTH, i as i32 / WIDTH).unwrap();
+
+        if before.glyph == ' ' {
+            assert!(after.color.r == 0 && after.color.b > 0, "cell {}: {:?}", i, after);
+        } else if before.color == Color::new(255, 0, 0) {
+            assert_eq!(after, before);
+        } else {
+            // Partly covered: the far quad only fills the remaining samples.
+            edges += 1;
+            assert!(after.color.r > 0 && after.color.b > 0, "cell {}: {:?}", i, after);
+
+            // More than half the samples are the near triangle's, so its
+            // glyph stays.
+            if before.color.r > 128 {
+                assert_eq!(after.glyph, before.glyph, "cell {}", i);
+            }
+        }
+    }
+
+    assert!(edges > 0);
+}
+
+#[test]
+fn snapshot_diff_reports_cells() {
+    let report = snapshot::diff("ab\ncd\n", "ab\nxd\n").unwrap();
+
+    assert!(report.starts_with("1 cell(s) differ"));
+    assert!(report.contains("(0, 1): expected Some('c'), got Some('x')"));
+    assert!(snapshot::diff("ab\n", "ab\n").is_none());
+}
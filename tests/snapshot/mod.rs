@@ -0,0 +1,85 @@
+// Golden-file snapshot harness. A rendered CharGrid is compared against
+// tests/golden/<name>.txt; set UPDATE_GOLDENS=1 to rewrite the goldens from
+// the current output instead of comparing.
+use ncurses_rendering_engine::rasterizer::CharGrid::*;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+pub const UPDATE_VAR: &str = "UPDATE_GOLDENS";
+
+pub fn goldenPath(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.txt", name))
+}
+
+fn shouldUpdate() -> bool {
+    match env::var(UPDATE_VAR) {
+        Ok(value) => !value.is_empty() && value != "0",
+        Err(_) => false
+    }
+}
+
+// Describes every cell that differs, followed by both frames with the
+// mismatching rows marked.
+pub fn diff(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let expectedRows: Vec<&str> = expected.lines().collect();
+    let actualRows: Vec<&str> = actual.lines().collect();
+    let rows = expectedRows.len().max(actualRows.len());
+
+    let mut cells = String::new();
+    let mut frames = String::new();
+    let mut mismatches = 0;
+
+    for y in 0..rows {
+        let e: Vec<char> = expectedRows.get(y).map(|r| r.chars().collect()).unwrap_or_default();
+        let a: Vec<char> = actualRows.get(y).map(|r| r.chars().collect()).unwrap_or_default();
+        let mut rowDiffers = false;
+
+        for x in 0..e.len().max(a.len()) {
+            let ec = e.get(x).copied();
+            let ac = a.get(x).copied();
+
+            if ec != ac {
+                rowDiffers = true;
+                mismatches += 1;
+                cells.push_str(&format!("  ({}, {}): expected {:?}, got {:?}\n", x, y, ec, ac));
+            }
+        }
+
+        let marker = if rowDiffers { '>' } else { ' ' };
+        frames.push_str(&format!("{} {:3} |{}|\n", marker, y, e.iter().collect::<String>()));
+        if rowDiffers {
+            frames.push_str(&format!("{} {:3} |{}|\n", marker, y, a.iter().collect::<String>()));
+        }
+    }
+
+    Some(format!("{} cell(s) differ:\n{}\nexpected / actual:\n{}", mismatches, cells, frames))
+}
+
+pub fn assertSnapshot(name: &str, grid: &CharGrid) {
+    let path = goldenPath(name);
+    let actual = format!("{}\n", grid);
+
+    if shouldUpdate() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(_) => panic!("missing golden {}; run with {}=1 to create it", path.display(), UPDATE_VAR)
+    };
+
+    if let Some(report) = diff(&expected, &actual) {
+        panic!("snapshot '{}' does not match {}\n{}\nrun with {}=1 to accept the new output",
+            name, path.display(), report, UPDATE_VAR);
+    }
+}
@@ -0,0 +1,52 @@
+#![allow(non_snake_case)]
+extern crate ncurses_rendering_engine;
+
+use ncurses_rendering_engine::rasterizer::{CharGrid::*, FrameBuffer::*, RenderTarget::*};
+
+fn glyph(glyph: char) -> Cell {
+    Cell { glyph, depth: 0.5, ..Default::default() }
+}
+
+#[test]
+fn char_grid_keeps_presented_glyphs() {
+    let mut fb = FrameBuffer::new(4, 2);
+    fb.setCell(0, 0, glyph('#'));
+    fb.setCell(3, 0, glyph('*'));
+    fb.setCell(1, 1, glyph('.'));
+
+    let mut grid = CharGrid::new(4, 2);
+    fb.present(&mut grid);
+
+    assert_eq!(grid.getSize(), (4, 2));
+    assert_eq!(grid.getGlyph(3, 0), Some('*'));
+    assert_eq!(grid.getRow(1), Some(String::from(" .  ")));
+
+    // Rows are joined by newlines, without one at the end.
+    assert_eq!(grid.to_string(), "#  *\n .  ");
+}
+
+#[test]
+fn char_grid_ignores_cells_outside_it() {
+    let mut grid = CharGrid::new(3, 2);
+
+    grid.putCell(-1, 0, &glyph('x'));
+    grid.putCell(3, 1, &glyph('x'));
+    grid.putCell(0, 2, &glyph('x'));
+    assert_eq!(grid, CharGrid::new(3, 2));
+
+    assert_eq!(grid.getGlyph(3, 0), None);
+    assert_eq!(grid.getGlyph(0, -1), None);
+    assert_eq!(grid.getRow(2), None);
+
+    // A larger frame buffer is cut down to the grid.
+    let mut fb = FrameBuffer::new(5, 4);
+    fb.setCell(2, 1, glyph('@'));
+    fb.setCell(4, 3, glyph('@'));
+    fb.present(&mut grid);
+    assert_eq!(grid.to_string(), "   \n  @");
+
+    // Negative sizes give an empty grid.
+    let empty = CharGrid::new(-2, 5);
+    assert_eq!((empty.getWidth(), empty.getHeight()), (0, 5));
+    assert_eq!(empty.getRow(0), Some(String::new()));
+}